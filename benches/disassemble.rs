@@ -0,0 +1,56 @@
+use std::time::Instant;
+
+use genesis_rs::m68k::{arch, dis};
+
+// Linear sweep over a 4 MB image, the largest standard Mega Drive cartridge.
+// Set GENESIS_BENCH_ROM to a ROM file to measure real code instead of the
+// generated image.
+const SIZE: usize = 4 * 1024 * 1024;
+
+fn image() -> Vec<u8> {
+    if let Ok(path) = std::env::var("GENESIS_BENCH_ROM") {
+        return std::fs::read(path).expect("could not read GENESIS_BENCH_ROM");
+    }
+    // Deterministic pseudo random words: every opcode pattern gets exercised,
+    // including the unknown ones that have to try all their candidates.
+    let mut state = 0x2545_f491_u32;
+    let mut bytes = Vec::with_capacity(SIZE);
+    while bytes.len() < SIZE {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        bytes.extend_from_slice(&state.to_be_bytes());
+    }
+    bytes
+}
+
+fn main() {
+    let bytes = image();
+
+    let start = Instant::now();
+    dis::decode(&[0x4e, 0x71], 0, arch::M68000).unwrap();
+    println!("dispatch table built in {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let (mut offset, mut decoded, mut unknown) = (0, 0, 0);
+    while offset < bytes.len() {
+        match dis::decode(&bytes[offset..], offset as u32, arch::M68000) {
+            Ok(insn) => {
+                decoded += 1;
+                offset += insn.length;
+            }
+            Err(_) => {
+                unknown += 1;
+                offset += 2;
+            }
+        }
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "disassembled {} bytes in {:?}: {} instructions, {} undecodable words",
+        bytes.len(),
+        elapsed,
+        decoded,
+        unknown
+    );
+}
@@ -0,0 +1,2 @@
+pub mod m68k;
+pub mod md;
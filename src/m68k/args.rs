@@ -0,0 +1,205 @@
+use super::operand::EffectiveAddress;
+
+// Ref: the operand specifier language is documented at the top of binutils
+// include/opcode/m68k.h. Each operand is two characters: what kind of operand
+// it is, followed by the place in the instruction it is stored.
+
+// Which effective address modes an operand accepts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AddressingClass {
+    All,                    // '*'
+    AlterableMemory,        // '~'
+    Alterable,              // '%'
+    Data,                   // ';'
+    DataNotImmediate,       // '@'
+    Control,                // '!'
+    AlterableControl,       // '&'
+    AlterableData,          // '$'
+    AlterableControlOrData, // '?'
+    ControlOrData,          // '/'
+    Memory,                 // '|'
+    Save,                   // '>'
+    Restore,                // '<'
+    // The reduced operand sets of ColdFire move, bit and MAC instructions,
+    // named by their specifier letter.
+    ColdFire(u8),
+}
+
+impl AddressingClass {
+    pub fn from_char(c: u8) -> Option<Self> {
+        Some(match c {
+            b'*' => Self::All,
+            b'~' => Self::AlterableMemory,
+            b'%' => Self::Alterable,
+            b';' => Self::Data,
+            b'@' => Self::DataNotImmediate,
+            b'!' => Self::Control,
+            b'&' => Self::AlterableControl,
+            b'$' => Self::AlterableData,
+            b'?' => Self::AlterableControlOrData,
+            b'/' => Self::ControlOrData,
+            b'|' => Self::Memory,
+            b'>' => Self::Save,
+            b'<' => Self::Restore,
+            b'm' | b'n' | b'o' | b'p' | b'q' | b'v' | b'b' | b'w' | b'y' | b'z' | b'4' => {
+                Self::ColdFire(c)
+            }
+            _ => return None,
+        })
+    }
+    // Bit n is set when mode n, as numbered by EffectiveAddress::mode, is
+    // accepted.
+    pub fn modes(&self) -> u16 {
+        match self {
+            Self::All => 0b1111_1111_1111,
+            Self::AlterableMemory => 0b0001_1111_1100,
+            Self::Alterable => 0b0001_1111_1111,
+            Self::Data => 0b1111_1111_1101,
+            Self::DataNotImmediate => 0b0111_1111_1101,
+            Self::Control => 0b0111_1110_0100,
+            Self::AlterableControl => 0b0001_1110_0100,
+            Self::AlterableData => 0b0001_1111_1101,
+            Self::AlterableControlOrData => 0b0001_1110_0101,
+            Self::ControlOrData => 0b0111_1110_0101,
+            Self::Memory => 0b0111_1110_0100,
+            Self::Save => 0b0001_1111_0100,
+            Self::Restore => 0b0111_1110_1100,
+            Self::ColdFire(c) => match c {
+                b'm' => 0b0000_0001_1111,
+                b'n' => 0b0010_0010_0000,
+                b'o' => 0b1101_1100_0000,
+                b'p' => 0b0000_0011_1111,
+                b'q' => 0b0000_0011_1101,
+                b'v' => 0b0001_1011_1101,
+                b'b' => 0b0010_0011_1101,
+                b'w' => 0b0010_0011_1100,
+                b'y' => 0b0000_0010_0100,
+                b'z' => 0b0010_0010_0100,
+                b'4' => 0b0000_0011_1100,
+                _ => 0,
+            },
+        }
+    }
+    pub fn allows_mode(&self, mode: u8) -> bool {
+        mode < 12 && self.modes() & (1 << mode) != 0
+    }
+    pub fn allows(&self, ea: &EffectiveAddress) -> bool {
+        self.allows_mode(ea.mode())
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Ea(AddressingClass),
+    DataRegister,         // 'D'
+    AddressRegister,      // 'A'
+    Register,             // 'R', data or address register in four bits
+    RegisterIndirect,     // 'r', cas2 only
+    AddressIndirect,      // 'a'
+    PostIncrement,        // '+'
+    PreDecrement,         // '-'
+    Displacement,         // 'd', register plus a full word displacement
+    AbsoluteLong,         // '_', move16 only
+    FpRegister,           // 'F'
+    FpControlList,        // 's'
+    BitField,             // 'O', offset or width
+    Quick,                // 'Q', 1-8
+    MoveQuick,            // 'M', signed byte, or a MAC shift with place 'h'
+    Mov3q,                // 'x', -1 or 1-7
+    TrapVector,           // 'T'
+    Immediate,            // '#'
+    FpImmediate,          // '^'
+    Branch,               // 'B'
+    Ccr,                  // 'C'
+    Sr,                   // 'S'
+    Usp,                  // 'U'
+    ControlRegister,      // 'J'
+    RegisterList,         // 'L'
+    RegisterListReversed, // 'l'
+    Cache,                // 'c'
+    KFactor,              // 'k'
+    CoprocessorId,        // 'I'
+    CoprocessorEt,        // 'j'
+    CoprocessorCommand,   // 'K'
+    MmuRegister(u8),      // 'X', 'Y', 'Z', 'W', '0'-'3'
+    Val,                  // 'V'
+    FunctionCode,         // 'f'
+    Level,                // 't'
+    MacAcc,               // 'E'
+    MacAccN,              // 'e'
+    Macsr,                // 'G'
+    Mask,                 // 'H'
+    MacAccExt,            // 'g'
+    ScaleFactor,          // 'i'
+    RegisterHalf,         // 'u'
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Specifier {
+    pub kind: Kind,
+    // Where the operand lives, e.g. 's' for the low bits of the first word or
+    // '1' for the second word shifted 12. Effective addresses and immediates
+    // use it for the operand size instead.
+    pub place: u8,
+}
+
+impl Specifier {
+    pub fn parse(kind: u8, place: u8) -> Option<Self> {
+        let kind = match kind {
+            b'D' => Kind::DataRegister,
+            b'A' => Kind::AddressRegister,
+            b'R' => Kind::Register,
+            b'r' => Kind::RegisterIndirect,
+            b'a' => Kind::AddressIndirect,
+            b'+' => Kind::PostIncrement,
+            b'-' => Kind::PreDecrement,
+            b'd' => Kind::Displacement,
+            b'_' => Kind::AbsoluteLong,
+            b'F' => Kind::FpRegister,
+            b's' => Kind::FpControlList,
+            b'O' => Kind::BitField,
+            b'Q' => Kind::Quick,
+            b'M' => Kind::MoveQuick,
+            b'x' => Kind::Mov3q,
+            b'T' => Kind::TrapVector,
+            b'#' if b"sC83bwWl".contains(&place) => Kind::Immediate,
+            b'^' if b"sC83bwWl".contains(&place) => Kind::FpImmediate,
+            b'B' if b"bBwWlLCgc".contains(&place) => Kind::Branch,
+            b'C' => Kind::Ccr,
+            b'S' => Kind::Sr,
+            b'U' => Kind::Usp,
+            b'J' => Kind::ControlRegister,
+            b'L' if b"w38".contains(&place) => Kind::RegisterList,
+            b'l' if b"w38".contains(&place) => Kind::RegisterListReversed,
+            b'c' => Kind::Cache,
+            b'k' if place == b'k' || place == b'C' => Kind::KFactor,
+            b'I' => Kind::CoprocessorId,
+            b'j' => Kind::CoprocessorEt,
+            b'K' => Kind::CoprocessorCommand,
+            b'X' | b'Y' | b'Z' | b'W' | b'0' | b'1' | b'2' | b'3' => Kind::MmuRegister(kind),
+            b'V' => Kind::Val,
+            b'f' => Kind::FunctionCode,
+            b't' => Kind::Level,
+            b'E' => Kind::MacAcc,
+            b'e' => Kind::MacAccN,
+            b'G' => Kind::Macsr,
+            b'H' => Kind::Mask,
+            b'g' => Kind::MacAccExt,
+            b'i' => Kind::ScaleFactor,
+            b'u' => Kind::RegisterHalf,
+            _ => Kind::Ea(AddressingClass::from_char(kind)?),
+        };
+        Some(Self { kind, place })
+    }
+}
+
+// Parses a whole OpCode args string. Returns None if any specifier is not
+// one the disassembler understands, which rules the entry out for decoding.
+pub fn parse_args(args: &str) -> Option<Vec<Specifier>> {
+    // A leading '.' marks the ColdFire coprocessor forms and carries no operand.
+    let args = args.strip_prefix('.').unwrap_or(args).as_bytes();
+    if !args.len().is_multiple_of(2) {
+        return None;
+    }
+    args.chunks(2).map(|s| Specifier::parse(s[0], s[1])).collect()
+}
@@ -1,5 +1,670 @@
-pub static REGISTER_NAMES: &[&str] = &[
-    "%d0", "%d1", "%d2", "%d3", "%d4", "%d5", "%d6", "%d7",
-    "%a0", "%a1", "%a2", "%a3", "%a4", "%a5", "%a6", "%a7",
-    "%ps", "%pc"
-];
+use std::fmt::{self, Display, Formatter};
+
+use super::arch;
+use super::args::{Kind, Specifier};
+use super::dispatch::DispatchTable;
+use super::opcode::OpCode;
+use super::operand::{
+    BitFieldValue, EffectiveAddress, Immediate, IndexBase, IndexRegister, KFactor, MacRegister,
+    MemoryIndirect, MmuRegister, Operand, Register,
+};
+use super::syntax::{self, Syntax};
+
+// Ref: the matching and operand extraction follow binutils opcodes/m68k-dis.c,
+// which the OP_CODES table was transcribed for.
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    // The instruction continues past the end of the supplied bytes.
+    Truncated,
+    // No table entry accepts this opcode word.
+    Unknown(u16),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "instruction truncated"),
+            Self::Unknown(word) => write!(f, "unknown opcode {:#06x}", word),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+#[derive(Clone, Debug)]
+pub struct Instruction {
+    pub address: u32,
+    pub opcode: &'static OpCode,
+    pub operands: Vec<Operand>,
+    pub length: usize,
+}
+
+impl Instruction {
+    pub fn mnemonic(&self) -> &'static str {
+        self.opcode.name()
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", syntax::Formatter::new(Syntax::Mit).format(self))
+    }
+}
+
+// Decodes the instruction at the start of `bytes`, which is located at `pc`,
+// considering only table entries available on the processors in `arch`, a
+// mask of `arch` constants. Mega Drive code should use `arch::M68000`.
+pub fn decode(bytes: &[u8], pc: u32, arch: u32) -> Result<Instruction, DecodeError> {
+    decode_in(DispatchTable::get(arch), bytes, pc)
+}
+
+// Decodes like `decode`, for the processors `table` was built for.
+pub fn decode_in(table: &DispatchTable, bytes: &[u8], pc: u32) -> Result<Instruction, DecodeError> {
+    let arch = table.arch();
+    let words = Words(bytes);
+    let first = words.word(0).map_err(|_| DecodeError::Truncated)?;
+    let second = words.word(2).ok();
+    let mut truncated = false;
+    for entry in table.candidates(first) {
+        let opcode = entry.opcode;
+        let second = match second {
+            Some(word) => word,
+            None if opcode.has_second_word() => {
+                truncated = true;
+                continue;
+            }
+            None => 0,
+        };
+        if !opcode.matches(first, second) {
+            continue;
+        }
+        match decode_with(opcode, &entry.specifiers, &words, pc, arch) {
+            Ok(insn) => return Ok(insn),
+            Err(Mismatch::Rejected) => continue,
+            Err(Mismatch::Truncated) => return Err(DecodeError::Truncated),
+        }
+    }
+    Err(if truncated { DecodeError::Truncated } else { DecodeError::Unknown(first) })
+}
+
+enum Mismatch {
+    // The entry does not describe these bytes; try the next one.
+    Rejected,
+    Truncated,
+}
+
+struct Words<'a>(&'a [u8]);
+
+impl Words<'_> {
+    fn byte(&self, offset: usize) -> Result<u32, Mismatch> {
+        self.0.get(offset).map(|&b| b as u32).ok_or(Mismatch::Truncated)
+    }
+    fn word(&self, offset: usize) -> Result<u16, Mismatch> {
+        match self.0.get(offset..offset + 2) {
+            Some(b) => Ok(u16::from_be_bytes([b[0], b[1]])),
+            None => Err(Mismatch::Truncated),
+        }
+    }
+    fn long(&self, offset: usize) -> Result<u32, Mismatch> {
+        Ok(((self.word(offset)? as u32) << 16) | self.word(offset + 2)? as u32)
+    }
+    fn bytes<const N: usize>(&self, offset: usize) -> Result<[u8; N], Mismatch> {
+        match self.0.get(offset..offset + N) {
+            Some(b) => Ok(b.try_into().unwrap()),
+            None => Err(Mismatch::Truncated),
+        }
+    }
+    // Extracts an operand field from the fixed opcode words by its place code.
+    fn field(&self, place: u8, bits: u32) -> Result<u32, Mismatch> {
+        let b = |offset| self.byte(offset);
+        let value = match place {
+            b's' | b'f' => b(1)?,
+            b'd' => self.word(0)? as u32 >> 9,
+            b'x' => self.word(0)? as u32 >> 6,
+            b'k' => b(3)? >> 4,
+            b'C' => b(3)?,
+            b'1' => self.word(2)? as u32 >> 12,
+            b'2' => self.word(2)? as u32 >> 6,
+            b'3' | b'j' => self.word(2)? as u32,
+            b'4' => self.word(4)? as u32 >> 12,
+            b'5' => self.word(4)? as u32 >> 6,
+            b'6' => self.word(4)? as u32,
+            b'7' => self.word(2)? as u32 >> 7,
+            b'8' => self.word(2)? as u32 >> 10,
+            b'9' => self.word(2)? as u32 >> 5,
+            b'e' => b(1)? >> 6,
+            b'E' => self.word(2)? as u32 >> 9,
+            // ColdFire MAC fields, several of them split across both words.
+            b'/' => b(3)? >> 5,
+            b'G' => ((b(3)? >> 3) & 2) | ((!b(1)? >> 7) & 1),
+            b'H' => ((b(3)? >> 3) & 2) | ((b(1)? >> 7) & 1),
+            b']' => b(0)? >> 2,
+            b'I' | b'h' => b(2)? >> 2,
+            b'F' => b(0)? >> 1,
+            b'm' => (b(1)? & 0x40) >> 3 | (b(0)? >> 1) & 7 | (b(3)? & 0x80) >> 3,
+            b'n' => (b(1)? & 0x40) >> 3 | (b(0)? >> 1) & 7,
+            b'o' => b(2)? >> 4 | (b(3)? & 0x80) >> 3,
+            b'M' => b(1)? & 0xf | (b(3)? & 0x40) >> 2,
+            b'N' => b(3)? & 0xf | (b(3)? & 0x40) >> 2,
+            _ => return Err(Mismatch::Rejected),
+        };
+        Ok(value & ((1 << bits) - 1))
+    }
+    // Mode and register of an effective address; destination fields of move
+    // are stored register first.
+    fn ea_field(&self, place: u8) -> Result<(u8, u8), Mismatch> {
+        if place == b'd' {
+            let value = self.field(b'x', 6)? as u8;
+            Ok((value & 7, value >> 3))
+        } else {
+            let value = self.field(b's', 6)? as u8;
+            Ok((value >> 3, value & 7))
+        }
+    }
+}
+
+fn decode_with(
+    opcode: &'static OpCode,
+    specs: &[Specifier],
+    words: &Words,
+    pc: u32,
+    arch: u32,
+) -> Result<Instruction, Mismatch> {
+    for spec in specs {
+        let valid = match spec.kind {
+            Kind::Ea(class) => {
+                let (mode, reg) = words.ea_field(spec.place)?;
+                class.allows_mode(if mode == 7 { 7 + reg } else { mode })
+            }
+            // A single floating point control register moves to or from any
+            // effective address; lists of them have their own entries.
+            Kind::FpControlList if spec.place == b'8' => words.field(b'8', 3)?.count_ones() <= 1,
+            // Only the default coprocessor id is an FPU instruction.
+            Kind::CoprocessorId => words.field(b'd', 3)? == 1,
+            _ => true,
+        };
+        if !valid {
+            return Err(Mismatch::Rejected);
+        }
+    }
+    let mut p = fixed_length(opcode, specs);
+    let mut operands = Vec::with_capacity(specs.len());
+    for &spec in specs {
+        operands.extend(decode_operand(opcode, spec, words, &mut p, pc, arch)?);
+    }
+    // Fixed words no operand looked at still belong to the instruction.
+    words.word(p - 2)?;
+    Ok(Instruction {
+        address: pc,
+        opcode,
+        operands,
+        length: p,
+    })
+}
+
+// Length of the opcode words and in-place immediates, before any effective
+// address extension words.
+fn fixed_length(opcode: &OpCode, specs: &[Specifier]) -> usize {
+    let mut p = 2;
+    for &Specifier { kind, place } in specs {
+        match kind {
+            Kind::Immediate if place == b'l' => p = p.max(6),
+            Kind::Immediate if place != b'C' && place != b'8' => p = p.max(4),
+            // Floating point immediates follow the command word.
+            Kind::FpImmediate if place == b'l' => p = p.max(8),
+            Kind::FpImmediate => p = p.max(6),
+            Kind::RegisterList | Kind::RegisterListReversed if place == b'w' => p = p.max(4),
+            _ => {}
+        }
+        match place {
+            b'1' | b'2' | b'3' | b'7' | b'8' | b'9' | b'i' => p = p.max(4),
+            b'4' | b'5' | b'6' => p = p.max(6),
+            _ => {}
+        }
+    }
+    if opcode.has_second_word() {
+        p = p.max(4);
+    }
+    if is_lpstop(opcode) {
+        p = p.max(6);
+    }
+    p
+}
+
+// lpstop carries its one word operand after a fixed second opcode word.
+fn is_lpstop(opcode: &OpCode) -> bool {
+    opcode.mask() & 0xffff == 0xffff && opcode.args().starts_with("#w")
+}
+
+// Decodes one operand. Specifiers that only qualify another operand, such as
+// the coprocessor id or the second half of a bit field, produce nothing.
+fn decode_operand(
+    opcode: &OpCode,
+    spec: Specifier,
+    words: &Words,
+    p: &mut usize,
+    pc: u32,
+    arch: u32,
+) -> Result<Option<Operand>, Mismatch> {
+    let Specifier { kind, place } = spec;
+    let field = |bits| words.field(place, bits);
+    let ea = |ea| Operand::Ea(ea);
+    Ok(Some(match kind {
+        // MAC loads keep the mask flag in the second word.
+        Kind::Ea(_) if place == b'/' => {
+            let ea = decode_ea(b's', words, p, pc, arch)?;
+            if words.field(b'/', 1)? != 0 {
+                Operand::Masked(ea)
+            } else {
+                Operand::Ea(ea)
+            }
+        }
+        Kind::Ea(_) => ea(decode_ea(place, words, p, pc, arch)?),
+        Kind::DataRegister => ea(EffectiveAddress::DataRegister(field(3)? as u8)),
+        Kind::AddressRegister => ea(EffectiveAddress::AddressRegister(field(3)? as u8)),
+        Kind::Register => ea(match Register::from_index(field(4)? as u8) {
+            Register::Data(n) => EffectiveAddress::DataRegister(n),
+            Register::Address(n) => EffectiveAddress::AddressRegister(n),
+        }),
+        Kind::RegisterIndirect => Operand::RegisterIndirect(Register::from_index(field(4)? as u8)),
+        Kind::AddressIndirect => ea(EffectiveAddress::Indirect(field(3)? as u8)),
+        Kind::PostIncrement => ea(EffectiveAddress::PostIncrement(field(3)? as u8)),
+        Kind::PreDecrement => ea(EffectiveAddress::PreDecrement(field(3)? as u8)),
+        Kind::Displacement => {
+            let disp = words.word(*p)? as i16;
+            *p += 2;
+            ea(EffectiveAddress::Displacement {
+                reg: field(3)? as u8,
+                disp,
+            })
+        }
+        Kind::AbsoluteLong => {
+            let addr = words.long(*p)?;
+            *p += 4;
+            ea(EffectiveAddress::AbsoluteLong(addr))
+        }
+        Kind::FpRegister => Operand::FpRegister(field(3)? as u8),
+        Kind::FpControlList => Operand::FpControlList(field(3)? as u8),
+        // Offset and width are always specified together, offset first.
+        Kind::BitField if place == b'2' => Operand::BitField {
+            offset: bit_field_value(words.field(b'2', 6)?, false),
+            width: bit_field_value(words.field(b'3', 6)?, true),
+        },
+        Kind::BitField => return Ok(None),
+        Kind::Quick => match field(3)? as i32 {
+            0 if place != b's' => Operand::Quick(8),
+            value => Operand::Quick(value),
+        },
+        // MAC shift of the product.
+        Kind::MoveQuick if place == b'h' && field(1)? == 0 => Operand::ShiftLeft,
+        Kind::MoveQuick if place == b'h' => Operand::ShiftRight,
+        Kind::MoveQuick => Operand::Quick(field(8)? as u8 as i8 as i32),
+        Kind::Mov3q => match field(3)? as i32 {
+            0 => Operand::Quick(-1),
+            value => Operand::Quick(value),
+        },
+        Kind::TrapVector => Operand::Quick(field(4)? as i32),
+        Kind::Immediate | Kind::FpImmediate => {
+            let at = if kind == Kind::FpImmediate || is_lpstop(opcode) { 4 } else { 2 };
+            match place {
+                b's' => Operand::Quick(field(4)? as i32),
+                b'C' => Operand::Quick(field(7)? as i32),
+                b'8' => Operand::Quick(field(3)? as i32),
+                b'3' => Operand::Quick(field(8)? as i32),
+                b'b' => ea(EffectiveAddress::Immediate(Immediate::Byte(words.word(at)? as u8))),
+                b'w' | b'W' => ea(EffectiveAddress::Immediate(Immediate::Word(words.word(at)?))),
+                b'l' => ea(EffectiveAddress::Immediate(Immediate::Long(words.long(at)?))),
+                _ => return Err(Mismatch::Rejected),
+            }
+        }
+        Kind::Branch => Operand::Branch(branch_target(place, words, p, pc)?),
+        Kind::Ccr => Operand::Ccr,
+        Kind::Sr => Operand::Sr,
+        Kind::Usp => Operand::Usp,
+        Kind::ControlRegister => Operand::Control(field(12)? as u16),
+        Kind::RegisterList | Kind::RegisterListReversed => match place {
+            b'w' => {
+                let mut list = words.word(2)?;
+                *p = (*p).max(4);
+                // The predecrement form stores the mask with a7 in bit 0.
+                if kind == Kind::RegisterListReversed {
+                    list = list.reverse_bits();
+                }
+                Operand::RegisterList(list)
+            }
+            b'3' => {
+                let mut list = field(8)? as u8;
+                // fmovem to memory stores fp0 in bit 7.
+                if kind == Kind::RegisterListReversed {
+                    list = list.reverse_bits();
+                }
+                Operand::FpRegisterList(list)
+            }
+            _ => Operand::FpControlList(field(3)? as u8),
+        },
+        Kind::Cache => Operand::Cache(field(2)? as u8),
+        Kind::KFactor if place == b'k' => Operand::KFactor(KFactor::Dynamic(field(3)? as u8)),
+        // Seven bit two's complement.
+        Kind::KFactor => Operand::KFactor(KFactor::Static(((field(7)? as u8) << 1) as i8 >> 1)),
+        Kind::CoprocessorId => return Ok(None),
+        Kind::CoprocessorEt => Operand::Quick(field(3)? as i32 + 1),
+        Kind::CoprocessorCommand => Operand::Quick(field(9)? as i32),
+        Kind::MmuRegister(which) => {
+            // The transparent translation registers are always numbered from
+            // bit 10, whatever place the table gives.
+            let place = if which == b'X' { b'8' } else { place };
+            let breakpoint = ((words.byte(3)? >> 2) & 7) as u8;
+            Operand::Mmu(match words.field(place, 5)? {
+                0x02 => MmuRegister::Tt0,
+                0x03 => MmuRegister::Tt1,
+                0x10 => MmuRegister::Tc,
+                0x11 => MmuRegister::Drp,
+                0x12 => MmuRegister::Srp,
+                0x13 => MmuRegister::Crp,
+                0x14 => MmuRegister::Cal,
+                0x15 => MmuRegister::Val,
+                0x16 => MmuRegister::Scc,
+                0x17 => MmuRegister::Ac,
+                0x18 => MmuRegister::Psr,
+                0x19 => MmuRegister::Pcsr,
+                0x1c => MmuRegister::Bad(breakpoint),
+                0x1d => MmuRegister::Bac(breakpoint),
+                other => MmuRegister::Other(other as u8),
+            })
+        }
+        Kind::Val => Operand::Mmu(MmuRegister::Val),
+        Kind::FunctionCode => Operand::FunctionCode(field(5)? as u8),
+        Kind::Level => Operand::Quick(field(3)? as i32),
+        Kind::MacAcc => Operand::Mac(MacRegister::Acc),
+        Kind::MacAccN => Operand::Mac(MacRegister::AccN(field(2)? as u8)),
+        Kind::Macsr => Operand::Mac(MacRegister::Macsr),
+        Kind::Mask => Operand::Mac(MacRegister::Mask),
+        Kind::MacAccExt if field(1)? != 0 => Operand::Mac(MacRegister::AccExt01),
+        Kind::MacAccExt => Operand::Mac(MacRegister::AccExt23),
+        Kind::ScaleFactor => match field(2)? {
+            1 => Operand::ShiftLeft,
+            3 => Operand::ShiftRight,
+            _ => return Err(Mismatch::Rejected),
+        },
+        Kind::RegisterHalf => {
+            let value = field(5)? as u8;
+            Operand::RegisterHalf {
+                register: Register::from_index(value & 0xf),
+                upper: value & 0x10 != 0,
+            }
+        }
+    }))
+}
+
+// Bit 5 selects a data register; an immediate width of 0 means 32.
+fn bit_field_value(value: u32, width: bool) -> BitFieldValue {
+    match value {
+        v if v & 0x20 != 0 => BitFieldValue::DataRegister(v as u8 & 7),
+        0 if width => BitFieldValue::Immediate(32),
+        v => BitFieldValue::Immediate(v as u8),
+    }
+}
+
+fn branch_target(place: u8, words: &Words, p: &mut usize, pc: u32) -> Result<u32, Mismatch> {
+    let base = pc.wrapping_add(*p as u32);
+    let first = words.word(0)?;
+    let disp = match place {
+        b'B' => first as u8 as i8 as i32,
+        b'b' => {
+            let value = words.word(*p)? as u8 as i8 as i32;
+            *p += 2;
+            value
+        }
+        b'w' | b'W' => {
+            let value = words.word(*p)? as i16 as i32;
+            *p += 2;
+            value
+        }
+        b'l' | b'L' | b'C' => {
+            let value = words.long(*p)? as i32;
+            *p += 4;
+            value
+        }
+        b'g' => match first as u8 {
+            0x00 => {
+                let value = words.word(*p)? as i16 as i32;
+                *p += 2;
+                value
+            }
+            0xff => {
+                let value = words.long(*p)? as i32;
+                *p += 4;
+                value
+            }
+            byte => byte as i8 as i32,
+        },
+        b'c' if first & 0x40 != 0 => {
+            let value = words.long(*p)? as i32;
+            *p += 4;
+            value
+        }
+        b'c' => {
+            let value = words.word(*p)? as i16 as i32;
+            *p += 2;
+            value
+        }
+        _ => return Err(Mismatch::Rejected),
+    };
+    Ok(base.wrapping_add(disp as u32))
+}
+
+fn decode_ea(place: u8, words: &Words, p: &mut usize, pc: u32, arch: u32) -> Result<EffectiveAddress, Mismatch> {
+    let (mode, reg) = words.ea_field(place)?;
+    let base = pc.wrapping_add(*p as u32);
+    Ok(match (mode, reg) {
+        (0, _) => EffectiveAddress::DataRegister(reg),
+        (1, _) => EffectiveAddress::AddressRegister(reg),
+        (2, _) => EffectiveAddress::Indirect(reg),
+        (3, _) => EffectiveAddress::PostIncrement(reg),
+        (4, _) => EffectiveAddress::PreDecrement(reg),
+        (5, _) => {
+            let disp = words.word(*p)? as i16;
+            *p += 2;
+            EffectiveAddress::Displacement { reg, disp }
+        }
+        (6, _) => decode_indexed(Some(reg), words, p, base, arch)?,
+        (7, 0) => {
+            let addr = words.word(*p)? as i16;
+            *p += 2;
+            EffectiveAddress::AbsoluteShort(addr)
+        }
+        (7, 1) => {
+            let addr = words.long(*p)?;
+            *p += 4;
+            EffectiveAddress::AbsoluteLong(addr)
+        }
+        (7, 2) => {
+            let disp = words.word(*p)? as i16;
+            *p += 2;
+            EffectiveAddress::PcDisplacement {
+                disp,
+                target: base.wrapping_add(disp as i32 as u32),
+            }
+        }
+        (7, 3) => decode_indexed(None, words, p, base, arch)?,
+        (7, 4) => {
+            let (imm, size) = match place {
+                b'b' => (Immediate::Byte(words.word(*p)? as u8), 2),
+                b'w' => (Immediate::Word(words.word(*p)?), 2),
+                b'l' => (Immediate::Long(words.long(*p)?), 4),
+                b'f' => (Immediate::Single(words.long(*p)?), 4),
+                b'F' => (Immediate::Double(u64::from_be_bytes(words.bytes(*p)?)), 8),
+                b'x' => (Immediate::Extended(words.bytes(*p)?), 12),
+                b'p' => (Immediate::Packed(words.bytes(*p)?), 12),
+                _ => return Err(Mismatch::Rejected),
+            };
+            *p += size;
+            EffectiveAddress::Immediate(imm)
+        }
+        _ => return Err(Mismatch::Rejected),
+    })
+}
+
+// Address register (mode 6) or PC (mode 7.3) indexed addressing, with either
+// the 68000 brief or the 68020 full extension word. `base` is the address of
+// the extension word.
+fn decode_indexed(
+    reg: Option<u8>,
+    words: &Words,
+    p: &mut usize,
+    base: u32,
+    arch: u32,
+) -> Result<EffectiveAddress, Mismatch> {
+    let ext = words.word(*p)?;
+    *p += 2;
+    // The 68000 and 68010 have neither index scaling nor the full format.
+    let scaled = ext & 0x600 != 0;
+    let full = ext & 0x100 != 0;
+    if (scaled && arch & (arch::M68020UP | arch::CPU32 | arch::FIDO_A | arch::MCF_MASK) == 0)
+        || (full && arch & arch::M68020UP == 0)
+    {
+        return Err(Mismatch::Rejected);
+    }
+    let index = IndexRegister {
+        register: Register::from_index((ext >> 12) as u8),
+        long: ext & 0x800 != 0,
+        scale: 1 << ((ext >> 9) & 3),
+    };
+    if !full {
+        let disp = ext as u8 as i8;
+        return Ok(match reg {
+            Some(reg) => EffectiveAddress::Indexed { reg, disp, index },
+            None => EffectiveAddress::PcIndexed {
+                disp,
+                index,
+                base: base.wrapping_add(disp as i32 as u32),
+            },
+        });
+    }
+    let index_suppressed = ext & 0x40 != 0;
+    let selector = ext & 7;
+    if ext & 8 != 0 || (ext >> 4) & 3 == 0 || selector == 4 || (index_suppressed && selector > 3) {
+        return Err(Mismatch::Rejected);
+    }
+    let base_disp = match (ext >> 4) & 3 {
+        2 => {
+            let disp = words.word(*p)? as i16 as i32;
+            *p += 2;
+            disp
+        }
+        3 => {
+            let disp = words.long(*p)? as i32;
+            *p += 4;
+            disp
+        }
+        _ => 0,
+    };
+    let indirect = if selector == 0 {
+        None
+    } else {
+        let outer_disp = match selector & 3 {
+            2 => {
+                let disp = words.word(*p)? as i16 as i32;
+                *p += 2;
+                disp
+            }
+            3 => {
+                let disp = words.long(*p)? as i32;
+                *p += 4;
+                disp
+            }
+            _ => 0,
+        };
+        Some(MemoryIndirect {
+            post_indexed: selector & 4 != 0,
+            outer_disp,
+        })
+    };
+    let base = match (reg, ext & 0x80 != 0) {
+        (Some(reg), false) => IndexBase::Address(reg),
+        (Some(reg), true) => IndexBase::SuppressedAddress(reg),
+        (None, false) => IndexBase::Pc(base),
+        (None, true) => IndexBase::SuppressedPc,
+    };
+    Ok(EffectiveAddress::Extended {
+        base,
+        base_disp,
+        index: (!index_suppressed).then_some(index),
+        indirect,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(bytes: &[u8], pc: u32, arch: u32) -> String {
+        decode(bytes, pc, arch).unwrap().to_string()
+    }
+
+    #[test]
+    fn movem_predecrement_list_is_reversed() {
+        // The same mask names opposite ends of the register list.
+        assert_eq!(
+            text(&[0x48, 0xE7, 0xC0, 0x00], 0, arch::M68000),
+            "moveml %d0-%d1,%a7@-"
+        );
+        assert_eq!(
+            text(&[0x4C, 0xDF, 0xC0, 0x00], 0, arch::M68000),
+            "moveml %a7@+,%a6-%a7"
+        );
+    }
+
+    #[test]
+    fn pc_relative_targets_are_absolute() {
+        assert_eq!(
+            text(&[0x41, 0xFA, 0x00, 0x06], 0x100, arch::M68000),
+            "lea %pc@(0x108),%a0"
+        );
+        assert_eq!(
+            text(&[0x30, 0x3B, 0x00, 0x04], 0x200, arch::M68000),
+            "movew %pc@(0x206,%d0:w),%d0"
+        );
+    }
+
+    #[test]
+    fn extension_words_follow_the_processor() {
+        // Scaled index and the full format are 68020 additions.
+        let scaled = [0x22, 0x30, 0x0C, 0x00];
+        let full = [0x22, 0x30, 0x09, 0x20, 0x00, 0x00];
+        assert_eq!(text(&scaled, 0, arch::M68020), "movel %a0@(0,%d0:l:4),%d1");
+        assert_eq!(decode(&full, 0, arch::M68020).unwrap().length, 6);
+        assert_eq!(
+            decode(&scaled, 0, arch::M68000).unwrap_err(),
+            DecodeError::Unknown(0x2230)
+        );
+        assert_eq!(
+            decode(&full, 0, arch::M68000).unwrap_err(),
+            DecodeError::Unknown(0x2230)
+        );
+    }
+
+    #[test]
+    fn coldfire_data_register_needs_register_mode() {
+        let immediate = [0x00, 0x00, 0x00, 0x01];
+        let addil = |first: [u8; 2]| decode(&[&first[..], &immediate].concat(), 0, arch::MCFISA_A);
+        assert_eq!(addil([0x06, 0x80]).unwrap().to_string(), "addil #1,%d0");
+        assert_eq!(
+            addil([0x06, 0x90]).unwrap_err(),
+            DecodeError::Unknown(0x0690)
+        );
+    }
+
+    #[test]
+    fn short_input_is_truncated() {
+        assert_eq!(
+            decode(&[0x4E], 0, arch::M68000).unwrap_err(),
+            DecodeError::Truncated
+        );
+        assert_eq!(
+            decode(&[0x06, 0x80, 0, 0], 0, arch::M68000).unwrap_err(),
+            DecodeError::Truncated
+        );
+    }
+}
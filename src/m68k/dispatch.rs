@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use super::arch;
+use super::args::{parse_args, Specifier};
+use super::opcode::{OpCode, OP_CODES};
+
+// An OP_CODES entry the disassembler can decode, with its args string already
+// parsed.
+#[derive(Debug)]
+pub struct Entry {
+    pub opcode: &'static OpCode,
+    pub specifiers: Vec<Specifier>,
+}
+
+// Decodable entries in table order. Entries whose args cannot be parsed, or
+// that repeat a register in two places for the assembler's benefit (places
+// 'D' and 't'), are left out.
+fn entries() -> &'static [Entry] {
+    static ENTRIES: OnceLock<Vec<Entry>> = OnceLock::new();
+    ENTRIES.get_or_init(|| {
+        OP_CODES
+            .iter()
+            .filter_map(|opcode| {
+                let specifiers = parse_args(opcode.args())?;
+                if specifiers.iter().any(|s| s.place == b'D' || s.place == b't') {
+                    return None;
+                }
+                Some(Entry { opcode, specifiers })
+            })
+            .collect()
+    })
+}
+
+// Masks whose table is found without taking a lock: one per processor.
+const PROCESSORS: [u32; 12] = [
+    arch::M68000,
+    arch::M68010,
+    arch::M68020,
+    arch::M68030,
+    arch::M68040,
+    arch::M68060,
+    arch::CPU32,
+    arch::FIDO_A,
+    arch::MCFISA_A,
+    arch::MCFISA_AA,
+    arch::MCFISA_B,
+    arch::MCFISA_C,
+];
+
+// Candidate entries for every possible first opcode word, for one
+// architecture mask.
+pub struct DispatchTable {
+    arch: u32,
+    // Candidates for word w are entries[starts[w]..starts[w + 1]].
+    starts: Vec<u32>,
+    // Indices into entries(), in table order for each word.
+    entries: Vec<u16>,
+}
+
+impl DispatchTable {
+    // The shared table for `arch`, built on first use and kept for the rest
+    // of the program. Other masks than single processors are looked up in a
+    // map, and each distinct one keeps its own table.
+    pub fn get(arch: u32) -> &'static DispatchTable {
+        static PROCESSOR_TABLES: [OnceLock<DispatchTable>; PROCESSORS.len()] =
+            [const { OnceLock::new() }; PROCESSORS.len()];
+        static OTHER_TABLES: OnceLock<RwLock<HashMap<u32, &'static DispatchTable>>> =
+            OnceLock::new();
+        if let Some(index) = PROCESSORS.iter().position(|&processor| processor == arch) {
+            return PROCESSOR_TABLES[index].get_or_init(|| Self::new(arch));
+        }
+        let tables = OTHER_TABLES.get_or_init(Default::default);
+        if let Some(table) = tables.read().unwrap().get(&arch) {
+            return table;
+        }
+        tables
+            .write()
+            .unwrap()
+            .entry(arch)
+            .or_insert_with(|| Box::leak(Box::new(Self::new(arch))))
+    }
+
+    pub fn new(arch: u32) -> Self {
+        let all = entries();
+        let selected: Vec<usize> = (0..all.len())
+            .filter(|&i| all[i].opcode.arch() & arch != 0)
+            .collect();
+        // Counting sort: size each word's bucket, then fill the buckets in
+        // table order so the first match still wins.
+        let mut starts = vec![0u32; 0x10001];
+        for &i in &selected {
+            for_each_first_word(all[i].opcode, |word| starts[word as usize + 1] += 1);
+        }
+        for word in 0..0x10000 {
+            starts[word + 1] += starts[word];
+        }
+        let mut next = starts.clone();
+        let mut entries = vec![0u16; starts[0x10000] as usize];
+        for &i in &selected {
+            for_each_first_word(all[i].opcode, |word| {
+                entries[next[word as usize] as usize] = i as u16;
+                next[word as usize] += 1;
+            });
+        }
+        Self {
+            arch,
+            starts,
+            entries,
+        }
+    }
+
+    pub fn arch(&self) -> u32 {
+        self.arch
+    }
+
+    // Entries whose first word matches `first`, in table order.
+    pub fn candidates(&self, first: u16) -> impl Iterator<Item = &'static Entry> + '_ {
+        let range = self.starts[first as usize] as usize..self.starts[first as usize + 1] as usize;
+        let all = entries();
+        self.entries[range].iter().map(move |&i| &all[i as usize])
+    }
+}
+
+// Calls `f` with every first word the entry's mask and opcode accept.
+fn for_each_first_word(opcode: &OpCode, mut f: impl FnMut(u16)) {
+    let mask = (opcode.mask() >> 16) as u16;
+    let fixed = (opcode.opcode() >> 16) as u16 & mask;
+    let free = !mask;
+    // Walk every subset of the free bits.
+    let mut bits = free;
+    loop {
+        f(fixed | bits);
+        if bits == 0 {
+            break;
+        }
+        bits = (bits - 1) & free;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_are_kept_for_any_mask() {
+        for arch in [arch::M68000, arch::M68020 | arch::M68881] {
+            let table = DispatchTable::get(arch);
+            assert!(std::ptr::eq(table, DispatchTable::get(arch)));
+            assert_eq!(table.arch(), arch);
+        }
+    }
+
+    #[test]
+    fn candidates_follow_the_mask() {
+        let names = |arch: u32, first: u16| -> Vec<&str> {
+            let table = DispatchTable::get(arch);
+            table
+                .candidates(first)
+                .map(|entry| entry.opcode.name())
+                .collect()
+        };
+        assert!(names(arch::M68000, 0x4E71).contains(&"nop"));
+        // extbl is a 68020 addition.
+        assert!(!names(arch::M68000, 0x49C0).contains(&"extbl"));
+        assert!(names(arch::M68020, 0x49C0).contains(&"extbl"));
+    }
+}
@@ -1,5 +1,9 @@
 mod opcode;
+pub mod args;
 pub mod arch;
 pub mod cpu;
 pub mod dis;
+pub mod dispatch;
+pub mod operand;
+pub mod syntax;
 pub use opcode::*;
\ No newline at end of file
@@ -14,6 +14,36 @@ pub struct OpCode {
     size: u16,
 }
 
+impl OpCode {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+    pub fn args(&self) -> &'static str {
+        self.args
+    }
+    pub fn opcode(&self) -> u32 {
+        self.opcode
+    }
+    pub fn mask(&self) -> u32 {
+        self.mask
+    }
+    pub fn arch(&self) -> u32 {
+        self.arch
+    }
+    pub fn size(&self) -> u16 {
+        self.size
+    }
+    // The first opcode word always sits in the upper half; the lower half is
+    // only significant for the two(...) entries.
+    pub fn matches(&self, first: u16, second: u16) -> bool {
+        let word = ((first as u32) << 16) | second as u32;
+        word & self.mask == self.opcode
+    }
+    pub fn has_second_word(&self) -> bool {
+        self.mask & 0xffff != 0
+    }
+}
+
 impl Display for OpCode {
     fn fmt(&self, f: &mut Formatter) -> Result {
         write!(f, "{}: {}, {:#04X}, mask: {:#04X} size: {} arch: {}", self.name, self.args, self.opcode, self.mask, self.size, self.arch)
@@ -40,7 +70,7 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "addib", size: 4, opcode: one(0o003000), mask: one(0o177700), args: "#b$s", arch: arch::M68000UP},
     OpCode{name: "addiw", size: 4, opcode: one(0o003100), mask: one(0o177700), args: "#w$s", arch: arch::M68000UP},
     OpCode{name: "addil", size: 6, opcode: one(0o003200), mask: one(0o177700), args: "#l$s", arch: arch::M68000UP},
-    OpCode{name: "addil", size: 6, opcode: one(0o003200), mask: one(0o177700), args: "#lDs", arch: arch::MCFISA_A},
+    OpCode{name: "addil", size: 6, opcode: one(0o003200), mask: one(0o177770), args: "#lDs", arch: arch::MCFISA_A},
     OpCode{name: "addqb", size: 2, opcode: one(0o050000), mask: one(0o170700), args: "Qd$b", arch: arch::M68000UP},
     OpCode{name: "addqw", size: 2, opcode: one(0o050100), mask: one(0o170700), args: "Qd%w", arch: arch::M68000UP},
     OpCode{name: "addql", size: 2, opcode: one(0o050200), mask: one(0o170700), args: "Qd%l", arch: arch::M68000UP | arch::MCFISA_A},
@@ -56,7 +86,7 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "addw", size: 2,  opcode: one(0o150500), mask: one(0o170700), args: "Dd~w", arch: arch::M68000UP},
     OpCode{name: "addl", size: 2,  opcode: one(0o050200), mask: one(0o170700), args: "Qd%l", arch: arch::M68000UP | arch::MCFISA_A},
     OpCode{name: "addl", size: 6,  opcode: one(0o003200), mask: one(0o177700), args: "#l$s", arch: arch::M68000UP},
-    OpCode{name: "addl", size: 6,  opcode: one(0o003200), mask: one(0o177700), args: "#lDs", arch: arch::MCFISA_A},
+    OpCode{name: "addl", size: 6,  opcode: one(0o003200), mask: one(0o177770), args: "#lDs", arch: arch::MCFISA_A},
     OpCode{name: "addl", size: 2,  opcode: one(0o150700), mask: one(0o170700), args: "*lAd", arch: arch::M68000UP | arch::MCFISA_A},
     OpCode{name: "addl", size: 2,  opcode: one(0o150200), mask: one(0o170700), args: "*lDd", arch: arch::M68000UP | arch::MCFISA_A},
     OpCode{name: "addl", size: 2,  opcode: one(0o150600), mask: one(0o170700), args: "Dd~l", arch: arch::M68000UP | arch::MCFISA_A},
@@ -71,7 +101,7 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "andiw", size: 4, opcode: one(0o001100), mask: one(0o177700), args: "#w$s", arch: arch::M68000UP},
     OpCode{name: "andiw", size: 4, opcode: one(0o001174), mask: one(0o177777), args: "#wSs", arch: arch::M68000UP},
     OpCode{name: "andil", size: 6, opcode: one(0o001200), mask: one(0o177700), args: "#l$s", arch: arch::M68000UP},
-    OpCode{name: "andil", size: 6, opcode: one(0o001200), mask: one(0o177700), args: "#lDs", arch: arch::MCFISA_A},
+    OpCode{name: "andil", size: 6, opcode: one(0o001200), mask: one(0o177770), args: "#lDs", arch: arch::MCFISA_A},
     OpCode{name: "andi",  size: 4, opcode: one(0o001100), mask: one(0o177700), args: "#w$s", arch: arch::M68000UP},
     OpCode{name: "andi",  size: 4, opcode: one(0o001074), mask: one(0o177777), args: "#bCs", arch: arch::M68000UP},
     OpCode{name: "andi",  size: 4, opcode: one(0o001174), mask: one(0o177777), args: "#wSs", arch: arch::M68000UP},
@@ -86,7 +116,7 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "andw", size: 2, opcode: one(0o140100), mask: one(0o170700), args: ";wDd", arch: arch::M68000UP},
     OpCode{name: "andw", size: 2, opcode: one(0o140500), mask: one(0o170700), args: "Dd~w", arch: arch::M68000UP},
     OpCode{name: "andl", size: 6, opcode: one(0o001200), mask: one(0o177700), args: "#l$s", arch: arch::M68000UP},
-    OpCode{name: "andl", size: 6, opcode: one(0o001200), mask: one(0o177700), args: "#lDs", arch: arch::MCFISA_A},
+    OpCode{name: "andl", size: 6, opcode: one(0o001200), mask: one(0o177770), args: "#lDs", arch: arch::MCFISA_A},
     OpCode{name: "andl", size: 2, opcode: one(0o140200), mask: one(0o170700), args: ";lDd", arch: arch::M68000UP | arch::MCFISA_A},
     OpCode{name: "andl", size: 2, opcode: one(0o140600), mask: one(0o170700), args: "Dd~l", arch: arch::M68000UP | arch::MCFISA_A},
     OpCode{name: "and",  size: 4, opcode: one(0o001100), mask: one(0o177700), args: "#w$w", arch: arch::M68000UP},
@@ -210,7 +240,7 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "chk2b",   size: 4, opcode: two(0o000300, 0o004000), mask: two(0o177700, 0o7777), args: "!sR1", arch: arch::M68020UP | arch::CPU32 | arch::FIDO_A},
     OpCode{name: "chk2w",   size: 4, opcode: two(0o001300, 0o004000), mask: two(0o177700, 0o7777), args: "!sR1", arch: arch::M68020UP | arch::CPU32 | arch::FIDO_A},
     OpCode{name: "chk2l",   size: 4, opcode: two(0o002300, 0o004000), mask: two(0o177700, 0o7777), args: "!sR1", arch: arch::M68020UP | arch::CPU32 | arch::FIDO_A},
-    OpCode{name: "chkl",    size: 2, opcode: one(0o040400),	mask: one(0o170700), args: ";lDd", arch: arch::M68000UP},
+    OpCode{name: "chkl",    size: 2, opcode: one(0o040400),	mask: one(0o170700), args: ";lDd", arch: arch::M68020UP | arch::CPU32 | arch::FIDO_A},
     OpCode{name: "chkw",    size: 2, opcode: one(0o040600),	mask: one(0o170700), args: ";wDd", arch: arch::M68000UP},
 
     OpCode{name: "cinva", size: 2, opcode: one(0xf400|SCOPE_ALL), mask: one(0xff38), args: "ce", arch: arch::M68040UP},
@@ -229,28 +259,28 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "cmpaw", size: 2, opcode: one(0o130300), mask: one(0o170700), args: "*wAd", arch: arch::M68000UP},
     OpCode{name: "cmpal", size: 2, opcode: one(0o130700), mask: one(0o170700), args: "*lAd", arch: arch::M68000UP | arch::MCFISA_A},
     OpCode{name: "cmpib", size: 4, opcode: one(0o006000), mask: one(0o177700), args: "#b@s", arch: arch::M68000UP},
-    OpCode{name: "cmpib", size: 4, opcode: one(0o006000), mask: one(0o177700), args: "#bDs", arch: arch::MCFISA_B | arch::MCFISA_C},
+    OpCode{name: "cmpib", size: 4, opcode: one(0o006000), mask: one(0o177770), args: "#bDs", arch: arch::MCFISA_B | arch::MCFISA_C},
     OpCode{name: "cmpiw", size: 4, opcode: one(0o006100), mask: one(0o177700), args: "#w@s", arch: arch::M68000UP},
-    OpCode{name: "cmpiw", size: 4, opcode: one(0o006100), mask: one(0o177700), args: "#wDs", arch: arch::MCFISA_B | arch::MCFISA_C},
+    OpCode{name: "cmpiw", size: 4, opcode: one(0o006100), mask: one(0o177770), args: "#wDs", arch: arch::MCFISA_B | arch::MCFISA_C},
     OpCode{name: "cmpil", size: 6, opcode: one(0o006200), mask: one(0o177700), args: "#l@s", arch: arch::M68000UP},
-    OpCode{name: "cmpil", size: 6, opcode: one(0o006200), mask: one(0o177700), args: "#lDs", arch: arch::MCFISA_A},
+    OpCode{name: "cmpil", size: 6, opcode: one(0o006200), mask: one(0o177770), args: "#lDs", arch: arch::MCFISA_A},
     OpCode{name: "cmpmb", size: 2, opcode: one(0o130410), mask: one(0o170770), args: "+s+d", arch: arch::M68000UP},
     OpCode{name: "cmpmw", size: 2, opcode: one(0o130510), mask: one(0o170770), args: "+s+d", arch: arch::M68000UP},
     OpCode{name: "cmpml", size: 2, opcode: one(0o130610), mask: one(0o170770), args: "+s+d", arch: arch::M68000UP},
 /* The cmp opcode can generate the cmpa, cmpm, and cmpi instructions.  */
     OpCode{name: "cmpb", size: 4, opcode: one(0o006000), mask: one(0o177700), args: "#b@s", arch: arch::M68000UP},
-    OpCode{name: "cmpb", size: 4, opcode: one(0o006000), mask: one(0o177700), args: "#bDs", arch: arch::MCFISA_B | arch::MCFISA_C},
+    OpCode{name: "cmpb", size: 4, opcode: one(0o006000), mask: one(0o177770), args: "#bDs", arch: arch::MCFISA_B | arch::MCFISA_C},
     OpCode{name: "cmpb", size: 2, opcode: one(0o130410), mask: one(0o170770), args: "+s+d", arch: arch::M68000UP},
     OpCode{name: "cmpb", size: 2, opcode: one(0o130000), mask: one(0o170700), args: ";bDd", arch: arch::M68000UP},
     OpCode{name: "cmpb", size: 2, opcode: one(0o130000), mask: one(0o170700), args: "*bDd", arch: arch::MCFISA_B | arch::MCFISA_C},
     OpCode{name: "cmpw", size: 2, opcode: one(0o130300), mask: one(0o170700), args: "*wAd", arch: arch::M68000UP},
     OpCode{name: "cmpw", size: 4, opcode: one(0o006100), mask: one(0o177700), args: "#w@s", arch: arch::M68000UP},
-    OpCode{name: "cmpw", size: 4, opcode: one(0o006100), mask: one(0o177700), args: "#wDs", arch: arch::MCFISA_B | arch::MCFISA_C},
+    OpCode{name: "cmpw", size: 4, opcode: one(0o006100), mask: one(0o177770), args: "#wDs", arch: arch::MCFISA_B | arch::MCFISA_C},
     OpCode{name: "cmpw", size: 2, opcode: one(0o130510), mask: one(0o170770), args: "+s+d", arch: arch::M68000UP},
     OpCode{name: "cmpw", size: 2, opcode: one(0o130100), mask: one(0o170700), args: "*wDd", arch: arch::M68000UP | arch::MCFISA_B | arch::MCFISA_C},
     OpCode{name: "cmpl", size: 2, opcode: one(0o130700), mask: one(0o170700), args: "*lAd", arch: arch::M68000UP | arch::MCFISA_A},
     OpCode{name: "cmpl", size: 6, opcode: one(0o006200), mask: one(0o177700), args: "#l@s", arch: arch::M68000UP},
-    OpCode{name: "cmpl", size: 6, opcode: one(0o006200), mask: one(0o177700), args: "#lDs", arch: arch::MCFISA_A},
+    OpCode{name: "cmpl", size: 6, opcode: one(0o006200), mask: one(0o177770), args: "#lDs", arch: arch::MCFISA_A},
     OpCode{name: "cmpl", size: 2, opcode: one(0o130610), mask: one(0o170770), args: "+s+d", arch: arch::M68000UP},
     OpCode{name: "cmpl", size: 2, opcode: one(0o130200), mask: one(0o170700), args: "*lDd", arch: arch::M68000UP | arch::MCFISA_A},
     
@@ -310,7 +340,7 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "eoriw", size:  4,	opcode: one(0o005100), mask: one(0o177700), args: "#w$s", arch: arch::M68000UP},
     OpCode{name: "eoriw", size:  4,	opcode: one(0o005174), mask: one(0o177777), args: "#wSs", arch: arch::M68000UP},
     OpCode{name: "eoril", size:  6,	opcode: one(0o005200), mask: one(0o177700), args: "#l$s", arch: arch::M68000UP},
-    OpCode{name: "eoril", size:  6,	opcode: one(0o005200), mask: one(0o177700), args: "#lDs", arch: arch::MCFISA_A},
+    OpCode{name: "eoril", size:  6,	opcode: one(0o005200), mask: one(0o177770), args: "#lDs", arch: arch::MCFISA_A},
     OpCode{name: "eori",  size:  4,	opcode: one(0o005074), mask: one(0o177777), args: "#bCs", arch: arch::M68000UP},
     OpCode{name: "eori",  size:  4,	opcode: one(0o005174), mask: one(0o177777), args: "#wSs", arch: arch::M68000UP},
     OpCode{name: "eori",  size:  4,	opcode: one(0o005100), mask: one(0o177700), args: "#w$s", arch: arch::M68000UP},
@@ -322,7 +352,7 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "eorw",  size:  4, opcode: one(0o005174), mask: one(0o177777), args: "#wSs", arch: arch::M68000UP},
     OpCode{name: "eorw",  size:  2, opcode: one(0o130500), mask: one(0o170700), args: "Dd$s", arch: arch::M68000UP},
     OpCode{name: "eorl",  size:  6, opcode: one(0o005200), mask: one(0o177700), args: "#l$s", arch: arch::M68000UP},
-    OpCode{name: "eorl",  size:  6, opcode: one(0o005200), mask: one(0o177700), args: "#lDs", arch: arch::MCFISA_A},
+    OpCode{name: "eorl",  size:  6, opcode: one(0o005200), mask: one(0o177770), args: "#lDs", arch: arch::MCFISA_A},
     OpCode{name: "eorl",  size:  2, opcode: one(0o130600), mask: one(0o170700), args: "Dd$s", arch: arch::M68000UP | arch::MCFISA_A},
     OpCode{name: "eor",   size:  4,	opcode: one(0o005074), mask: one(0o177777), args: "#bCs", arch: arch::M68000UP},
     OpCode{name: "eor",   size:  4,	opcode: one(0o005174), mask: one(0o177777), args: "#wSs", arch: arch::M68000UP},
@@ -377,7 +407,7 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "fdabsd", size: 4, opcode: two(0xF000, 0x545c), mask: two(0xF1C0, 0xFC7F), args: "Ii;FF7", arch: arch::M68040UP},
     OpCode{name: "fdabsl", size: 4, opcode: two(0xF000, 0x405C), mask: two(0xF1C0, 0xFC7F), args: "IibsF7", arch: arch::CFLOAT },
     OpCode{name: "fdabsl", size: 4, opcode: two(0xF000, 0x405c), mask: two(0xF1C0, 0xFC7F), args: "Ii;lF7", arch: arch::M68040UP},
-    OpCode{name: "fdabsp", size: 4, opcode: two(0xF000, 0x4C5c), mask: two(0xF1C0, 0xFC7F), args: "Ii;pF7", arch: arch::M68040UP},
+    OpCode{name: "fdabsp", size: 4, opcode: two(0xF000, 0x4C5C), mask: two(0xF1C0, 0xFC7F), args: "Ii;pF7", arch: arch::M68040UP},
     OpCode{name: "fdabss", size: 4, opcode: two(0xF000, 0x445C), mask: two(0xF1C0, 0xFC7F), args: "IibsF7", arch: arch::CFLOAT },
     OpCode{name: "fdabss", size: 4, opcode: two(0xF000, 0x445c), mask: two(0xF1C0, 0xFC7F), args: "Ii;fF7", arch: arch::M68040UP},
     OpCode{name: "fdabsw", size: 4, opcode: two(0xF000, 0x505C), mask: two(0xF1C0, 0xFC7F), args: "IibsF7", arch: arch::CFLOAT },
@@ -1169,7 +1199,7 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "fdsubd",   size: 4, opcode: two(0xF000, 0x546c), mask: two(0xF1C0, 0xFC7F), args: "Ii;FF7", arch: arch::M68040UP},
     OpCode{name: "fdsubl",   size: 4, opcode: two(0xF000, 0x406c), mask: two(0xF1C0, 0xFC7F), args: "IibsF7", arch: arch::CFLOAT},
     OpCode{name: "fdsubl",   size: 4, opcode: two(0xF000, 0x406c), mask: two(0xF1C0, 0xFC7F), args: "Ii;lF7", arch: arch::M68040UP},
-    OpCode{name: "fdsubp",   size: 4, opcode: two(0xF000, 0x4C6c), mask: two(0xF1C0, 0xFC7F), args: "Ii;pF7", arch: arch::M68040UP},
+    OpCode{name: "fdsubp",   size: 4, opcode: two(0xF000, 0x4C6C), mask: two(0xF1C0, 0xFC7F), args: "Ii;pF7", arch: arch::M68040UP},
     OpCode{name: "fdsubs",   size: 4, opcode: two(0xF000, 0x446c), mask: two(0xF1C0, 0xFC7F), args: "IibsF7", arch: arch::CFLOAT},
     OpCode{name: "fdsubs",   size: 4, opcode: two(0xF000, 0x446c), mask: two(0xF1C0, 0xFC7F), args: "Ii;fF7", arch: arch::M68040UP},
     OpCode{name: "fdsubw",   size: 4, opcode: two(0xF000, 0x506c), mask: two(0xF1C0, 0xFC7F), args: "IibsF7", arch: arch::CFLOAT},
@@ -1484,10 +1514,10 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "move",   size: 2, opcode:	one(0o041300),  mask: one(0o177700), args: "Cs$s", arch: arch::M68010UP},
     OpCode{name: "move",   size: 2, opcode:	one(0o041300),  mask: one(0o177770), args: "CsDs", arch: arch::MCFISA_A},
     OpCode{name: "move",   size: 2, opcode:	one(0o042300),  mask: one(0o177700), args: ";wCd", arch: arch::M68000UP},
-    OpCode{name: "move",   size: 2, opcode:	one(0o042300),  mask: one(0o177700), args: "DsCd", arch: arch::MCFISA_A},
+    OpCode{name: "move",   size: 2, opcode:	one(0o042300),  mask: one(0o177770), args: "DsCd", arch: arch::MCFISA_A},
     OpCode{name: "move",   size: 4, opcode:	one(0o042374),  mask: one(0o177777), args: "#wCd", arch: arch::MCFISA_A},
     OpCode{name: "move",   size: 2, opcode:	one(0o043300),  mask: one(0o177700), args: ";wSd", arch: arch::M68000UP},
-    OpCode{name: "move",   size: 2, opcode:	one(0o043300),  mask: one(0o177700), args: "DsSd", arch: arch::MCFISA_A},
+    OpCode{name: "move",   size: 2, opcode:	one(0o043300),  mask: one(0o177770), args: "DsSd", arch: arch::MCFISA_A},
     OpCode{name: "move",   size: 4, opcode:	one(0o043374),  mask: one(0o177777), args: "#wSd", arch: arch::MCFISA_A},
     OpCode{name: "move",   size: 2, opcode:	one(0o047140),  mask: one(0o177770), args: "AsUd", arch: arch::M68000UP},
     OpCode{name: "move",   size: 2, opcode:	one(0o047150),  mask: one(0o177770), args: "UdAs", arch: arch::M68000UP},
@@ -1543,22 +1573,22 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "negb",   size: 2, opcode:	one(0o042000),	mask: one(0o177700), args: "$s", arch: arch::M68000UP},
     OpCode{name: "negw",   size: 2, opcode:	one(0o042100),	mask: one(0o177700), args: "$s", arch: arch::M68000UP},
     OpCode{name: "negl",   size: 2, opcode:	one(0o042200),	mask: one(0o177700), args: "$s", arch: arch::M68000UP},
-    OpCode{name: "negl",   size: 2, opcode:	one(0o042200),	mask: one(0o177700), args: "Ds", arch: arch::MCFISA_A},
+    OpCode{name: "negl",   size: 2, opcode:	one(0o042200),	mask: one(0o177770), args: "Ds", arch: arch::MCFISA_A},
     OpCode{name: "negxb",  size: 2, opcode:	one(0o040000),	mask: one(0o177700), args: "$s", arch: arch::M68000UP},
     OpCode{name: "negxw",  size: 2, opcode:	one(0o040100),	mask: one(0o177700), args: "$s", arch: arch::M68000UP},
     OpCode{name: "negxl",  size: 2, opcode:	one(0o040200),	mask: one(0o177700), args: "$s", arch: arch::M68000UP},
-    OpCode{name: "negxl",  size: 2, opcode:	one(0o040200),	mask: one(0o177700), args: "Ds", arch: arch::MCFISA_A},
+    OpCode{name: "negxl",  size: 2, opcode:	one(0o040200),	mask: one(0o177770), args: "Ds", arch: arch::MCFISA_A},
     OpCode{name: "nop",    size: 2, opcode:	one(0o047161),	mask: one(0o177777), args: "",   arch: arch::M68000UP | arch::MCFISA_A},
     OpCode{name: "notb",   size: 2, opcode:	one(0o043000),	mask: one(0o177700), args: "$s", arch: arch::M68000UP},
     OpCode{name: "notw",   size: 2, opcode:	one(0o043100),	mask: one(0o177700), args: "$s", arch: arch::M68000UP},
     OpCode{name: "notl",   size: 2, opcode:	one(0o043200),	mask: one(0o177700), args: "$s", arch: arch::M68000UP},
-    OpCode{name: "notl",   size: 2, opcode:	one(0o043200),	mask: one(0o177700), args: "Ds", arch: arch::MCFISA_A},
+    OpCode{name: "notl",   size: 2, opcode:	one(0o043200),	mask: one(0o177770), args: "Ds", arch: arch::MCFISA_A},
     OpCode{name: "orib",   size: 4, opcode:	one(0o000000),	mask: one(0o177700), args: "#b$s", arch: arch::M68000UP},
     OpCode{name: "orib",   size: 4, opcode:	one(0o000074),	mask: one(0o177777), args: "#bCs", arch: arch::M68000UP},
     OpCode{name: "oriw",   size: 4, opcode:	one(0o000100),	mask: one(0o177700), args: "#w$s", arch: arch::M68000UP},
     OpCode{name: "oriw",   size: 4, opcode:	one(0o000174),	mask: one(0o177777), args: "#wSs", arch: arch::M68000UP},
     OpCode{name: "oril",   size: 6, opcode:	one(0o000200),	mask: one(0o177700), args: "#l$s", arch: arch::M68000UP},
-    OpCode{name: "oril",   size: 6, opcode:	one(0o000200),	mask: one(0o177700), args: "#lDs", arch: arch::MCFISA_A},
+    OpCode{name: "oril",   size: 6, opcode:	one(0o000200),	mask: one(0o177770), args: "#lDs", arch: arch::MCFISA_A},
     OpCode{name: "ori",    size: 4, opcode:	one(0o000074),	mask: one(0o177777), args: "#bCs", arch: arch::M68000UP},
     OpCode{name: "ori",    size: 4, opcode:	one(0o000100),	mask: one(0o177700), args: "#w$s", arch: arch::M68000UP},
     OpCode{name: "ori",    size: 4, opcode:	one(0o000174),	mask: one(0o177777), args: "#wSs", arch: arch::M68000UP},
@@ -1572,7 +1602,7 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "orw", size: 2, opcode:	one(0o100100),	mask: one(0o170700), args: ";wDd", arch: arch::M68000UP},
     OpCode{name: "orw", size: 2, opcode:	one(0o100500),	mask: one(0o170700), args: "Dd~s", arch: arch::M68000UP},
     OpCode{name: "orl", size: 6, opcode:	one(0o000200),	mask: one(0o177700), args: "#l$s", arch: arch::M68000UP},
-    OpCode{name: "orl", size: 6, opcode:	one(0o000200),	mask: one(0o177700), args: "#lDs", arch: arch::MCFISA_A},
+    OpCode{name: "orl", size: 6, opcode:	one(0o000200),	mask: one(0o177770), args: "#lDs", arch: arch::MCFISA_A},
     OpCode{name: "orl", size: 2, opcode:	one(0o100200),	mask: one(0o170700), args: ";lDd", arch: arch::M68000UP | arch::MCFISA_A},
     OpCode{name: "orl", size: 2, opcode:	one(0o100600),	mask: one(0o170700), args: "Dd~s", arch: arch::M68000UP | arch::MCFISA_A},
     OpCode{name: "or",  size: 4, opcode:	one(0o000074),	mask: one(0o177777), args: "#bCs", arch: arch::M68000UP},
@@ -1630,7 +1660,7 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "pdbss", size: 4, opcode:	two(0xf048, 0x0004), mask: two(0xfff8, 0xffff), args: "DsBw", arch: arch::M68851},
     OpCode{name: "pdbwc", size: 4, opcode:	two(0xf048, 0x0009), mask: two(0xfff8, 0xffff), args: "DsBw", arch: arch::M68851},
     OpCode{name: "pdbws", size: 4, opcode:	two(0xf048, 0x0008), mask: two(0xfff8, 0xffff), args: "DsBw", arch: arch::M68851},
-    OpCode{name: "pea",   size: 2, opcode:	one(0044100),  mask: one(0177700), args: "!s", arch: arch::M68000UP | arch::MCFISA_A},
+    OpCode{name: "pea",   size: 2, opcode:	one(0o044100), mask: one(0o177700), args: "!s", arch: arch::M68000UP | arch::MCFISA_A},
     OpCode{name: "pflusha",  size: 2, opcode: one(0xf518), mask: one(0xfff8),  args: "",   arch: arch::M68040UP},
     OpCode{name: "pflusha",  size: 4, opcode: two(0xf000,0x2400),  mask: two(0xffff,0xffff), args: "",       arch: arch::M68030 | arch::M68851 },
     OpCode{name: "pflush",   size: 4, opcode: two(0xf000,0x3010),  mask: two(0xffc0,0xfe10), args: "T3T9",   arch: arch::M68030 | arch::M68851 },
@@ -1859,27 +1889,27 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "trapvsl", size: 6, opcode: one(0o054773), mask: one(0o177777), args: "#l", arch: arch::M68020UP | arch::CPU32 | arch::FIDO_A},
     OpCode{name: "trapv",   size: 2, opcode: one(0o047166), mask: one(0o177777), args: "", arch: arch::M68000UP},
     OpCode{name: "scc", size: 2, opcode: one(0o052300), mask: one(0o177700), args: "$s", arch: arch::M68000UP},
-    OpCode{name: "scc", size: 2, opcode: one(0o052300), mask: one(0o177700), args: "Ds", arch: arch::MCFISA_A},
+    OpCode{name: "scc", size: 2, opcode: one(0o052300), mask: one(0o177770), args: "Ds", arch: arch::MCFISA_A},
     OpCode{name: "scs", size: 2, opcode: one(0o052700), mask: one(0o177700), args: "$s", arch: arch::M68000UP},
-    OpCode{name: "scs", size: 2, opcode: one(0o052700), mask: one(0o177700), args: "Ds", arch: arch::MCFISA_A},
+    OpCode{name: "scs", size: 2, opcode: one(0o052700), mask: one(0o177770), args: "Ds", arch: arch::MCFISA_A},
     OpCode{name: "seq", size: 2, opcode: one(0o053700), mask: one(0o177700), args: "$s", arch: arch::M68000UP},
-    OpCode{name: "seq", size: 2, opcode: one(0o053700), mask: one(0o177700), args: "Ds", arch: arch::MCFISA_A},
+    OpCode{name: "seq", size: 2, opcode: one(0o053700), mask: one(0o177770), args: "Ds", arch: arch::MCFISA_A},
     OpCode{name: "sf",  size: 2, opcode: one(0o050700), mask: one(0o177700), args: "$s", arch: arch::M68000UP},
-    OpCode{name: "sf",  size: 2, opcode: one(0o050700), mask: one(0o177700), args: "Ds", arch: arch::MCFISA_A},
+    OpCode{name: "sf",  size: 2, opcode: one(0o050700), mask: one(0o177770), args: "Ds", arch: arch::MCFISA_A},
     OpCode{name: "sge", size: 2, opcode: one(0o056300), mask: one(0o177700), args: "$s", arch: arch::M68000UP},
-    OpCode{name: "sge", size: 2, opcode: one(0o056300), mask: one(0o177700), args: "Ds", arch: arch::MCFISA_A},
+    OpCode{name: "sge", size: 2, opcode: one(0o056300), mask: one(0o177770), args: "Ds", arch: arch::MCFISA_A},
     OpCode{name: "sgt", size: 2, opcode: one(0o057300), mask: one(0o177700), args: "$s", arch: arch::M68000UP},
-    OpCode{name: "sgt", size: 2, opcode: one(0o057300), mask: one(0o177700), args: "Ds", arch: arch::MCFISA_A},
+    OpCode{name: "sgt", size: 2, opcode: one(0o057300), mask: one(0o177770), args: "Ds", arch: arch::MCFISA_A},
     OpCode{name: "shi", size: 2, opcode: one(0o051300), mask: one(0o177700), args: "$s", arch: arch::M68000UP},
-    OpCode{name: "shi", size: 2, opcode: one(0o051300), mask: one(0o177700), args: "Ds", arch: arch::MCFISA_A},
+    OpCode{name: "shi", size: 2, opcode: one(0o051300), mask: one(0o177770), args: "Ds", arch: arch::MCFISA_A},
     OpCode{name: "sle", size: 2, opcode: one(0o057700), mask: one(0o177700), args: "$s", arch: arch::M68000UP},
-    OpCode{name: "sle", size: 2, opcode: one(0o057700), mask: one(0o177700), args: "Ds", arch: arch::MCFISA_A},
+    OpCode{name: "sle", size: 2, opcode: one(0o057700), mask: one(0o177770), args: "Ds", arch: arch::MCFISA_A},
     OpCode{name: "sls", size: 2, opcode: one(0o051700), mask: one(0o177700), args: "$s", arch: arch::M68000UP},
-    OpCode{name: "sls", size: 2, opcode: one(0o051700), mask: one(0o177700), args: "Ds", arch: arch::MCFISA_A},
+    OpCode{name: "sls", size: 2, opcode: one(0o051700), mask: one(0o177770), args: "Ds", arch: arch::MCFISA_A},
     OpCode{name: "slt", size: 2, opcode: one(0o056700), mask: one(0o177700), args: "$s", arch: arch::M68000UP},
-    OpCode{name: "slt", size: 2, opcode: one(0o056700), mask: one(0o177700), args: "Ds", arch: arch::MCFISA_A},
+    OpCode{name: "slt", size: 2, opcode: one(0o056700), mask: one(0o177770), args: "Ds", arch: arch::MCFISA_A},
     OpCode{name: "smi", size: 2, opcode: one(0o055700), mask: one(0o177700), args: "$s", arch: arch::M68000UP},
-    OpCode{name: "smi", size: 2, opcode: one(0o055700), mask: one(0o177700), args: "Ds", arch: arch::MCFISA_A},
+    OpCode{name: "smi", size: 2, opcode: one(0o055700), mask: one(0o177770), args: "Ds", arch: arch::MCFISA_A},
     OpCode{name: "sne", size: 2, opcode: one(0o053300), mask: one(0o177700), args: "$s", arch: arch::M68000UP},
     OpCode{name: "sne", size: 2, opcode: one(0o053300), mask: one(0o177770), args: "Ds", arch: arch::MCFISA_A},
     OpCode{name: "spl", size: 2, opcode: one(0o055300), mask: one(0o177700), args: "$s", arch: arch::M68000UP},
@@ -1898,7 +1928,7 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "subib",   size: 4, opcode: one(0o002000), mask: one(0o177700), args: "#b$s", arch: arch::M68000UP},
     OpCode{name: "subiw",   size: 4, opcode: one(0o002100), mask: one(0o177700), args: "#w$s", arch: arch::M68000UP},
     OpCode{name: "subil",   size: 6, opcode: one(0o002200), mask: one(0o177700), args: "#l$s", arch: arch::M68000UP},
-    OpCode{name: "subil",   size: 6, opcode: one(0o002200), mask: one(0o177700), args: "#lDs", arch: arch::MCFISA_A},
+    OpCode{name: "subil",   size: 6, opcode: one(0o002200), mask: one(0o177770), args: "#lDs", arch: arch::MCFISA_A},
     OpCode{name: "subqb",   size: 2, opcode: one(0o050400), mask: one(0o170700), args: "Qd%s", arch: arch::M68000UP},
     OpCode{name: "subqw",   size: 2, opcode: one(0o050500), mask: one(0o170700), args: "Qd%s", arch: arch::M68000UP},
     OpCode{name: "subql",   size: 2, opcode: one(0o050600), mask: one(0o170700), args: "Qd%s", arch: arch::M68000UP | arch::MCFISA_A},
@@ -1914,7 +1944,7 @@ pub static OP_CODES: &[OpCode] = &[
     OpCode{name: "subw",  size: 2, opcode: one(0o110500), mask: one(0o170700), args: "Dd~s", arch: arch::M68000UP},
     OpCode{name: "subl",  size: 2, opcode: one(0o050600), mask: one(0o170700), args: "Qd%s", arch: arch::M68000UP | arch::MCFISA_A},
     OpCode{name: "subl",  size: 6, opcode: one(0o002200), mask: one(0o177700), args: "#l$s", arch: arch::M68000UP},
-    OpCode{name: "subl",  size: 6, opcode: one(0o002200), mask: one(0o177700), args: "#lDs", arch: arch::MCFISA_A},
+    OpCode{name: "subl",  size: 6, opcode: one(0o002200), mask: one(0o177770), args: "#lDs", arch: arch::MCFISA_A},
     OpCode{name: "subl",  size: 2, opcode: one(0o110700), mask: one(0o170700), args: "*lAd", arch: arch::M68000UP | arch::MCFISA_A},
     OpCode{name: "subl",  size: 2, opcode: one(0o110200), mask: one(0o170700), args: "*lDd", arch: arch::M68000UP | arch::MCFISA_A},
     OpCode{name: "subl",  size: 2, opcode: one(0o110600), mask: one(0o170700), args: "Dd~s", arch: arch::M68000UP | arch::MCFISA_A},
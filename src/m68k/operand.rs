@@ -0,0 +1,265 @@
+use std::fmt::{Display, Formatter, Result};
+
+use super::cpu::REGISTER_NAMES;
+use super::syntax::{self, Syntax};
+
+// Display renders operands in MIT syntax, as objdump prints them; see
+// syntax::Formatter for the others.
+fn mit() -> syntax::Formatter {
+    syntax::Formatter::new(Syntax::Mit)
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Register {
+    Data(u8),
+    Address(u8),
+}
+
+impl Register {
+    // Registers encoded as 4 bits: d0-d7 followed by a0-a7.
+    pub fn from_index(index: u8) -> Self {
+        if index & 8 == 0 {
+            Self::Data(index & 7)
+        } else {
+            Self::Address(index & 7)
+        }
+    }
+    pub fn index(&self) -> u8 {
+        match self {
+            Self::Data(n) => *n,
+            Self::Address(n) => *n + 8,
+        }
+    }
+}
+
+impl Display for Register {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", REGISTER_NAMES[self.index() as usize])
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct IndexRegister {
+    pub register: Register,
+    pub long: bool,
+    pub scale: u8,
+}
+
+impl Display for IndexRegister {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", mit().index_register(self))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Immediate {
+    Byte(u8),
+    Word(u16),
+    Long(u32),
+    // Floating point immediates keep their raw encoding.
+    Single(u32),
+    Double(u64),
+    Extended([u8; 12]),
+    Packed([u8; 12]),
+}
+
+impl Immediate {
+    // Sign extended value of the integer immediates.
+    pub fn value(&self) -> Option<i32> {
+        match self {
+            Self::Byte(v) => Some(*v as i8 as i32),
+            Self::Word(v) => Some(*v as i16 as i32),
+            Self::Long(v) => Some(*v as i32),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Immediate {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", mit().immediate(self))
+    }
+}
+
+// Base of a 68020 full format extension word.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IndexBase {
+    Address(u8),
+    // The base register is suppressed but still selects mode 6.
+    SuppressedAddress(u8),
+    // Address of the extension word.
+    Pc(u32),
+    SuppressedPc,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MemoryIndirect {
+    // Whether the index is added after fetching the intermediate pointer.
+    pub post_indexed: bool,
+    pub outer_disp: i32,
+}
+
+// The twelve 68000 addressing modes, mode 7 split by register field.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EffectiveAddress {
+    DataRegister(u8),
+    AddressRegister(u8),
+    Indirect(u8),
+    PostIncrement(u8),
+    PreDecrement(u8),
+    Displacement { reg: u8, disp: i16 },
+    Indexed { reg: u8, disp: i8, index: IndexRegister },
+    AbsoluteShort(i16),
+    AbsoluteLong(u32),
+    PcDisplacement { disp: i16, target: u32 },
+    PcIndexed { disp: i8, index: IndexRegister, base: u32 },
+    Immediate(Immediate),
+    // 68020 full format extension, with optional memory indirection.
+    Extended {
+        base: IndexBase,
+        base_disp: i32,
+        index: Option<IndexRegister>,
+        indirect: Option<MemoryIndirect>,
+    },
+}
+
+impl EffectiveAddress {
+    // Mode number as used by the addressing class masks: 0-6 for the register
+    // modes, 7-11 for the mode 7 sub-modes.
+    pub fn mode(&self) -> u8 {
+        match self {
+            Self::DataRegister(_) => 0,
+            Self::AddressRegister(_) => 1,
+            Self::Indirect(_) => 2,
+            Self::PostIncrement(_) => 3,
+            Self::PreDecrement(_) => 4,
+            Self::Displacement { .. } => 5,
+            Self::Indexed { .. } => 6,
+            Self::AbsoluteShort(_) => 7,
+            Self::AbsoluteLong(_) => 8,
+            Self::PcDisplacement { .. } => 9,
+            Self::PcIndexed { .. } => 10,
+            Self::Immediate(_) => 11,
+            Self::Extended { base, .. } => match base {
+                IndexBase::Address(_) | IndexBase::SuppressedAddress(_) => 6,
+                IndexBase::Pc(_) | IndexBase::SuppressedPc => 10,
+            },
+        }
+    }
+}
+
+impl Display for EffectiveAddress {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", mit().effective_address(self))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BitFieldValue {
+    Immediate(u8),
+    DataRegister(u8),
+}
+
+// Destination precision of a packed decimal fmove.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KFactor {
+    Static(i8),
+    Dynamic(u8),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MmuRegister {
+    Tt0,
+    Tt1,
+    Tc,
+    Drp,
+    Srp,
+    Crp,
+    Cal,
+    Val,
+    Scc,
+    Ac,
+    Psr,
+    Pcsr,
+    Bad(u8),
+    Bac(u8),
+    Other(u8),
+}
+
+// ColdFire MAC/EMAC registers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MacRegister {
+    Acc,
+    AccN(u8),
+    Macsr,
+    Mask,
+    AccExt01,
+    AccExt23,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Operand {
+    Ea(EffectiveAddress),
+    // A MAC load operand with the mask register applied.
+    Masked(EffectiveAddress),
+    // Data or address register indirect, as used by cas2.
+    RegisterIndirect(Register),
+    // Immediate data held in the opcode word itself (addq, moveq, trap, ...).
+    Quick(i32),
+    Branch(u32),
+    // Bit n set for register n, d0 through a7, regardless of encoding order.
+    RegisterList(u16),
+    Ccr,
+    Sr,
+    Usp,
+    Control(u16),
+    FpRegister(u8),
+    // Bit n set for fp<n>.
+    FpRegisterList(u8),
+    // Bit 0 fpiar, bit 1 fpsr, bit 2 fpcr.
+    FpControlList(u8),
+    BitField { offset: BitFieldValue, width: BitFieldValue },
+    KFactor(KFactor),
+    Cache(u8),
+    Mmu(MmuRegister),
+    FunctionCode(u8),
+    Mac(MacRegister),
+    ShiftLeft,
+    ShiftRight,
+    RegisterHalf { register: Register, upper: bool },
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", mit().operand(self))
+    }
+}
+
+// Ref: movec register numbers from the MC68000 family programmer's manual.
+pub fn control_register_name(reg: u16) -> Option<&'static str> {
+    Some(match reg {
+        0x000 => "sfc",
+        0x001 => "dfc",
+        0x002 => "cacr",
+        0x003 => "tc",
+        0x004 => "itt0",
+        0x005 => "itt1",
+        0x006 => "dtt0",
+        0x007 => "dtt1",
+        0x008 => "buscr",
+        0x800 => "usp",
+        0x801 => "vbr",
+        0x802 => "caar",
+        0x803 => "msp",
+        0x804 => "isp",
+        0x805 => "mmusr",
+        0x806 => "urp",
+        0x807 => "srp",
+        0x808 => "pcr",
+        0xc04 => "flashbar",
+        0xc05 => "rambar",
+        0xffe => "cac",
+        0xfff => "mbb",
+        _ => return None,
+    })
+}
@@ -0,0 +1,580 @@
+use std::fmt::Write;
+
+use super::cpu::REGISTER_NAMES;
+use super::dis::Instruction;
+use super::operand::{
+    control_register_name, BitFieldValue, EffectiveAddress, Immediate, IndexBase, IndexRegister, KFactor,
+    MacRegister, MmuRegister, Operand, Register,
+};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Syntax {
+    // Motorola's own syntax from the programmer's reference manual:
+    // move.l (8,a0,d1.w*2),d0
+    Motorola,
+    // GNU as MIT syntax, as printed by objdump: movel %a0@(8,%d1:w:2),%d0
+    Mit,
+    // SN Systems asm68k and SNASM68K, old style operands and sp for a7:
+    // move.l 8(a0,d1.w*2),d0
+    Asm68k,
+    // vasm's mot syntax module, new style operands and sp for a7:
+    // move.l (8,a0,d1.w*2),d0
+    Vasm,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HexStyle {
+    Dollar, // $1f
+    ZeroX,  // 0x1f
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Case {
+    Lower,
+    Upper,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Radix {
+    Decimal,
+    Hex,
+}
+
+// Renders decoded instructions in a chosen assembler syntax. Addresses are
+// always hexadecimal and displacements always signed decimal; `radix` only
+// affects immediate data.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Formatter {
+    pub syntax: Syntax,
+    pub hex: HexStyle,
+    pub case: Case,
+    pub radix: Radix,
+}
+
+impl Formatter {
+    // Lower case with decimal immediates, and the hex style native to the
+    // syntax.
+    pub fn new(syntax: Syntax) -> Self {
+        Self {
+            syntax,
+            hex: if syntax == Syntax::Mit { HexStyle::ZeroX } else { HexStyle::Dollar },
+            case: Case::Lower,
+            radix: Radix::Decimal,
+        }
+    }
+
+    pub fn format(&self, insn: &Instruction) -> String {
+        let mut out = self.mnemonic(insn.mnemonic());
+        for (i, operand) in insn.operands.iter().enumerate() {
+            // Bit fields and k-factors are written straight after the
+            // effective address they qualify.
+            out.push_str(match operand {
+                Operand::BitField { .. } | Operand::KFactor(_) => "",
+                _ if i == 0 => " ",
+                _ => ",",
+            });
+            out.push_str(&self.operand(operand));
+        }
+        out
+    }
+
+    // Converts a table mnemonic such as "addl" or "bras" into the syntax's
+    // form, "add.l" and "bra.s" for the Motorola family.
+    pub fn mnemonic(&self, name: &str) -> String {
+        match split_size(name) {
+            Some((base, size)) if self.syntax != Syntax::Mit => self.cased(&format!("{}.{}", base, size)),
+            _ => self.cased(name),
+        }
+    }
+
+    pub fn operand(&self, operand: &Operand) -> String {
+        let mit = self.syntax == Syntax::Mit;
+        match operand {
+            Operand::Ea(ea) => self.effective_address(ea),
+            Operand::Masked(ea) => format!("{}&", self.effective_address(ea)),
+            Operand::RegisterIndirect(reg) if mit => match reg {
+                Register::Address(_) => format!("{}@", self.register(*reg)),
+                Register::Data(_) => format!("@({})", self.register(*reg)),
+            },
+            Operand::RegisterIndirect(reg) => format!("({})", self.register(*reg)),
+            Operand::Quick(value) => format!("#{}", self.signed_immediate(*value)),
+            Operand::Branch(target) => self.hex(*target),
+            Operand::RegisterList(list) => self.register_list(*list),
+            Operand::Ccr => self.name("ccr"),
+            Operand::Sr => self.name("sr"),
+            Operand::Usp => self.name("usp"),
+            Operand::Control(reg) => match control_register_name(*reg) {
+                Some(name) => self.name(name),
+                None => self.hex(*reg as u32),
+            },
+            Operand::FpRegister(n) => self.name(&format!("fp{}", n)),
+            Operand::FpRegisterList(list) => self.fp_register_list(*list),
+            Operand::FpControlList(list) => {
+                let names = ["fpiar", "fpsr", "fpcr"];
+                let selected: Vec<String> = (0..3)
+                    .filter(|bit| list & (1 << bit) != 0)
+                    .map(|bit| self.name(names[bit]))
+                    .collect();
+                selected.join("/")
+            }
+            Operand::BitField { offset, width } => {
+                format!("{{{}:{}}}", self.bit_field_value(offset), self.bit_field_value(width))
+            }
+            Operand::KFactor(KFactor::Static(k)) => format!("{{#{}}}", k),
+            Operand::KFactor(KFactor::Dynamic(n)) => format!("{{{}}}", self.register(Register::Data(*n))),
+            Operand::Cache(cache) => self.cased(["nc", "dc", "ic", "bc"][*cache as usize & 3]),
+            Operand::Mmu(reg) => self.mmu_register(reg),
+            Operand::FunctionCode(0) => self.name("sfc"),
+            Operand::FunctionCode(1) => self.name("dfc"),
+            Operand::FunctionCode(fc) => format!("#{}", fc),
+            Operand::Mac(reg) => self.mac_register(reg),
+            Operand::ShiftLeft => "<<".to_string(),
+            Operand::ShiftRight => ">>".to_string(),
+            Operand::RegisterHalf { register, upper } => {
+                let half = if *upper { "u" } else { "l" };
+                let half = if mit { self.cased(half) } else { format!(".{}", self.cased(half)) };
+                format!("{}{}", self.register(*register), half)
+            }
+        }
+    }
+
+    pub fn effective_address(&self, ea: &EffectiveAddress) -> String {
+        let a = |n: &u8| self.register(Register::Address(*n));
+        let d = |n: &u8| self.register(Register::Data(*n));
+        let abs_short = |addr: &i16| self.hex(*addr as i32 as u32);
+        match self.syntax {
+            Syntax::Mit => match ea {
+                EffectiveAddress::DataRegister(n) => d(n),
+                EffectiveAddress::AddressRegister(n) => a(n),
+                EffectiveAddress::Indirect(n) => format!("{}@", a(n)),
+                EffectiveAddress::PostIncrement(n) => format!("{}@+", a(n)),
+                EffectiveAddress::PreDecrement(n) => format!("{}@-", a(n)),
+                EffectiveAddress::Displacement { reg, disp } => format!("{}@({})", a(reg), disp),
+                EffectiveAddress::Indexed { reg, disp, index } => {
+                    format!("{}@({},{})", a(reg), disp, self.index_register(index))
+                }
+                EffectiveAddress::AbsoluteShort(addr) => abs_short(addr),
+                EffectiveAddress::AbsoluteLong(addr) => self.hex(*addr),
+                EffectiveAddress::PcDisplacement { target, .. } => {
+                    format!("{}@({})", self.name("pc"), self.hex(*target))
+                }
+                EffectiveAddress::PcIndexed { index, base, .. } => {
+                    format!("{}@({},{})", self.name("pc"), self.hex(*base), self.index_register(index))
+                }
+                EffectiveAddress::Immediate(imm) => self.immediate(imm),
+                EffectiveAddress::Extended { base, base_disp, index, indirect } => {
+                    let mut out = match base {
+                        IndexBase::Address(n) => format!("{}@({}", a(n), base_disp),
+                        IndexBase::SuppressedAddress(_) => format!("@({}", base_disp),
+                        IndexBase::Pc(pc) => {
+                            format!("{}@({}", self.name("pc"), self.hex(pc.wrapping_add(*base_disp as u32)))
+                        }
+                        IndexBase::SuppressedPc => format!("{}@({}", self.name("zpc"), base_disp),
+                    };
+                    let post_indexed = indirect.is_some_and(|i| i.post_indexed);
+                    if let (Some(index), false) = (index, post_indexed) {
+                        write!(out, ",{}", self.index_register(index)).unwrap();
+                    }
+                    out.push(')');
+                    if let Some(indirect) = indirect {
+                        write!(out, "@({}", indirect.outer_disp).unwrap();
+                        if let (Some(index), true) = (index, post_indexed) {
+                            write!(out, ",{}", self.index_register(index)).unwrap();
+                        }
+                        out.push(')');
+                    }
+                    out
+                }
+            },
+            _ => {
+                let old_style = self.syntax == Syntax::Asm68k;
+                // d16(an) or (d16,an)
+                let relative = |disp: String, base: String| {
+                    if old_style {
+                        format!("{}({})", disp, base)
+                    } else {
+                        format!("({},{})", disp, base)
+                    }
+                };
+                match ea {
+                    EffectiveAddress::DataRegister(n) => d(n),
+                    EffectiveAddress::AddressRegister(n) => a(n),
+                    EffectiveAddress::Indirect(n) => format!("({})", a(n)),
+                    EffectiveAddress::PostIncrement(n) => format!("({})+", a(n)),
+                    EffectiveAddress::PreDecrement(n) => format!("-({})", a(n)),
+                    EffectiveAddress::Displacement { reg, disp } => relative(disp.to_string(), a(reg)),
+                    EffectiveAddress::Indexed { reg, disp, index } => {
+                        relative(disp.to_string(), format!("{},{}", a(reg), self.index_register(index)))
+                    }
+                    EffectiveAddress::AbsoluteShort(addr) => format!("({}).{}", abs_short(addr), self.cased("w")),
+                    EffectiveAddress::AbsoluteLong(addr) => format!("({}).{}", self.hex(*addr), self.cased("l")),
+                    EffectiveAddress::PcDisplacement { target, .. } => relative(self.hex(*target), self.name("pc")),
+                    EffectiveAddress::PcIndexed { index, base, .. } => relative(
+                        self.hex(*base),
+                        format!("{},{}", self.name("pc"), self.index_register(index)),
+                    ),
+                    EffectiveAddress::Immediate(imm) => self.immediate(imm),
+                    // The 68020 memory indirect forms only exist in the new
+                    // style: ([bd,an,xn],od) and ([bd,an],xn,od).
+                    EffectiveAddress::Extended { base, base_disp, index, indirect } => {
+                        let mut inner = Vec::new();
+                        match base {
+                            IndexBase::Address(n) => {
+                                inner.push(base_disp.to_string());
+                                inner.push(a(n));
+                            }
+                            IndexBase::SuppressedAddress(_) => inner.push(base_disp.to_string()),
+                            IndexBase::Pc(pc) => {
+                                inner.push(self.hex(pc.wrapping_add(*base_disp as u32)));
+                                inner.push(self.name("pc"));
+                            }
+                            IndexBase::SuppressedPc => {
+                                inner.push(base_disp.to_string());
+                                inner.push(self.name("zpc"));
+                            }
+                        }
+                        let index = index.map(|index| self.index_register(&index));
+                        match indirect {
+                            None => {
+                                inner.extend(index);
+                                format!("({})", inner.join(","))
+                            }
+                            Some(indirect) => {
+                                let mut outer = Vec::new();
+                                if indirect.post_indexed {
+                                    outer.extend(index);
+                                } else {
+                                    inner.extend(index);
+                                }
+                                if indirect.outer_disp != 0 {
+                                    outer.push(indirect.outer_disp.to_string());
+                                }
+                                let mut out = format!("([{}]", inner.join(","));
+                                for part in outer {
+                                    write!(out, ",{}", part).unwrap();
+                                }
+                                out.push(')');
+                                out
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn register(&self, reg: Register) -> String {
+        let name = REGISTER_NAMES[reg.index() as usize];
+        match self.syntax {
+            Syntax::Mit => self.cased(name),
+            Syntax::Asm68k | Syntax::Vasm if reg == Register::Address(7) => self.cased("sp"),
+            _ => self.cased(&name[1..]),
+        }
+    }
+
+    // A register or other reserved name, with MIT's % prefix where needed.
+    fn name(&self, name: &str) -> String {
+        match self.syntax {
+            Syntax::Mit => format!("%{}", self.cased(name)),
+            _ => self.cased(name),
+        }
+    }
+
+    pub fn index_register(&self, index: &IndexRegister) -> String {
+        let size = self.cased(if index.long { "l" } else { "w" });
+        let reg = self.register(index.register);
+        match (self.syntax, index.scale) {
+            (Syntax::Mit, 1) => format!("{}:{}", reg, size),
+            (Syntax::Mit, scale) => format!("{}:{}:{}", reg, size, scale),
+            (_, 1) => format!("{}.{}", reg, size),
+            (_, scale) => format!("{}.{}*{}", reg, size, scale),
+        }
+    }
+
+    pub fn immediate(&self, imm: &Immediate) -> String {
+        let value = match (imm, self.radix) {
+            (Immediate::Byte(v), Radix::Hex) => self.hex(*v as u32),
+            (Immediate::Word(v), Radix::Hex) => self.hex(*v as u32),
+            (Immediate::Long(v), Radix::Hex) => self.hex(*v),
+            (Immediate::Single(bits), _) => format!("{:?}", f32::from_bits(*bits)),
+            (Immediate::Double(bits), _) => format!("{:?}", f64::from_bits(*bits)),
+            (Immediate::Extended(bytes) | Immediate::Packed(bytes), _) => {
+                let mut digits = String::new();
+                for b in bytes {
+                    match self.case {
+                        Case::Lower => write!(digits, "{:02x}", b).unwrap(),
+                        Case::Upper => write!(digits, "{:02X}", b).unwrap(),
+                    }
+                }
+                format!("{}{}", self.hex_prefix(), digits)
+            }
+            _ => imm.value().unwrap_or_default().to_string(),
+        };
+        format!("#{}", value)
+    }
+
+    fn signed_immediate(&self, value: i32) -> String {
+        match self.radix {
+            Radix::Decimal => value.to_string(),
+            Radix::Hex if value < 0 => format!("-{}", self.hex(value.unsigned_abs())),
+            Radix::Hex => self.hex(value as u32),
+        }
+    }
+
+    fn bit_field_value(&self, value: &BitFieldValue) -> String {
+        match value {
+            BitFieldValue::Immediate(value) => value.to_string(),
+            BitFieldValue::DataRegister(n) => self.register(Register::Data(*n)),
+        }
+    }
+
+    fn register_list(&self, list: u16) -> String {
+        if list == 0 {
+            return "#0".to_string();
+        }
+        let mut parts = Vec::new();
+        let mut reg = 0;
+        while reg < 16 {
+            if list & (1 << reg) == 0 {
+                reg += 1;
+                continue;
+            }
+            let start = reg;
+            // Ranges never span from the data into the address registers.
+            while reg < 15 && reg != 7 && list & (1 << (reg + 1)) != 0 {
+                reg += 1;
+            }
+            let first = self.register(Register::from_index(start));
+            if reg > start {
+                parts.push(format!("{}-{}", first, self.register(Register::from_index(reg))));
+            } else {
+                parts.push(first);
+            }
+            reg += 1;
+        }
+        parts.join("/")
+    }
+
+    fn fp_register_list(&self, list: u8) -> String {
+        if list == 0 {
+            return "#0".to_string();
+        }
+        let mut parts = Vec::new();
+        let mut reg = 0;
+        while reg < 8 {
+            if list & (1 << reg) == 0 {
+                reg += 1;
+                continue;
+            }
+            let start = reg;
+            while reg < 7 && list & (1 << (reg + 1)) != 0 {
+                reg += 1;
+            }
+            let first = self.name(&format!("fp{}", start));
+            if reg > start {
+                parts.push(format!("{}-{}", first, self.name(&format!("fp{}", reg))));
+            } else {
+                parts.push(first);
+            }
+            reg += 1;
+        }
+        parts.join("/")
+    }
+
+    fn mmu_register(&self, reg: &MmuRegister) -> String {
+        let name = match reg {
+            MmuRegister::Tt0 => "tt0".to_string(),
+            MmuRegister::Tt1 => "tt1".to_string(),
+            MmuRegister::Tc => "tc".to_string(),
+            MmuRegister::Drp => "drp".to_string(),
+            MmuRegister::Srp => "srp".to_string(),
+            MmuRegister::Crp => "crp".to_string(),
+            MmuRegister::Cal => "cal".to_string(),
+            MmuRegister::Val => "val".to_string(),
+            MmuRegister::Scc => "scc".to_string(),
+            MmuRegister::Ac => "ac".to_string(),
+            MmuRegister::Psr => "psr".to_string(),
+            MmuRegister::Pcsr => "pcsr".to_string(),
+            MmuRegister::Bad(n) => format!("bad{}", n),
+            MmuRegister::Bac(n) => format!("bac{}", n),
+            MmuRegister::Other(n) => return format!("<mmu register {}>", n),
+        };
+        self.name(&name)
+    }
+
+    fn mac_register(&self, reg: &MacRegister) -> String {
+        let name = match reg {
+            MacRegister::Acc => "acc".to_string(),
+            MacRegister::AccN(n) => format!("acc{}", n),
+            MacRegister::Macsr => "macsr".to_string(),
+            MacRegister::Mask => "mask".to_string(),
+            MacRegister::AccExt01 => "accext01".to_string(),
+            MacRegister::AccExt23 => "accext23".to_string(),
+        };
+        self.name(&name)
+    }
+
+    fn hex_prefix(&self) -> &'static str {
+        match self.hex {
+            HexStyle::Dollar => "$",
+            HexStyle::ZeroX => "0x",
+        }
+    }
+
+    fn hex(&self, value: u32) -> String {
+        match self.case {
+            Case::Lower => format!("{}{:x}", self.hex_prefix(), value),
+            Case::Upper => format!("{}{:X}", self.hex_prefix(), value),
+        }
+    }
+
+    fn cased(&self, text: &str) -> String {
+        match self.case {
+            Case::Lower => text.to_string(),
+            Case::Upper => text.to_uppercase(),
+        }
+    }
+}
+
+// Splits a table mnemonic into its base name and size suffix, if it has one.
+// Only known base names are split, so that "tas", "scs" and "bfexts" stay
+// whole while "blss" becomes "bls" "s".
+fn split_size(name: &str) -> Option<(&str, char)> {
+    let size = name.chars().last()?;
+    if !"bwlsxpd".contains(size) {
+        return None;
+    }
+    let base = &name[..name.len() - 1];
+    is_sized_base(base).then_some((base, size))
+}
+
+// Ref: condition codes from the MC68000 family programmer's reference manual.
+const CONDITIONS: &[&str] = &[
+    "t", "f", "hi", "ls", "cc", "cs", "ne", "eq", "vc", "vs", "pl", "mi", "ge", "lt", "gt", "le",
+];
+const FP_CONDITIONS: &[&str] = &[
+    "f", "eq", "ogt", "oge", "olt", "ole", "ogl", "or", "un", "ueq", "ugt", "uge", "ult", "ule", "ne", "t",
+    "sf", "seq", "gt", "ge", "lt", "le", "gl", "gle", "ngle", "ngl", "nle", "nlt", "nge", "ngt", "sne", "st",
+];
+const MMU_CONDITIONS: &[&str] = &[
+    "bs", "bc", "ls", "lc", "ss", "sc", "as", "ac", "ws", "wc", "is", "ic", "gs", "gc", "cs", "cc",
+];
+
+fn is_sized_base(base: &str) -> bool {
+    let conditional = |prefix: &str, conditions: &[&str]| {
+        base.strip_prefix(prefix).is_some_and(|cc| conditions.contains(&cc))
+    };
+    // bt and bf are spelled bra and (on the 68000) not at all.
+    (conditional("b", CONDITIONS) && base != "bt" && base != "bf")
+        || conditional("trap", CONDITIONS)
+        || conditional("fb", FP_CONDITIONS)
+        || conditional("ftrap", FP_CONDITIONS)
+        || conditional("pb", MMU_CONDITIONS)
+        || conditional("ptrap", MMU_CONDITIONS)
+        || matches!(
+            base,
+            "add" | "adda" | "addi" | "addq" | "addx" | "and" | "andi" | "asl" | "asr" | "bra" | "bsr"
+                | "cas" | "cas2" | "chk" | "chk2" | "clr" | "cmp" | "cmp2" | "cmpa" | "cmpi" | "cmpm"
+                | "cp0ld" | "cp0st" | "cp1ld" | "cp1st" | "divs" | "divsl" | "divu" | "divul" | "eor"
+                | "eori" | "ext" | "extb" | "link" | "lsl" | "lsr" | "mac" | "mov3q" | "movclr" | "move"
+                | "movea" | "movem" | "movep" | "moves" | "msac" | "muls" | "mulu" | "mvs" | "mvz"
+                | "neg" | "negx" | "not" | "or" | "ori" | "rems" | "remu" | "rol" | "ror" | "roxl"
+                | "roxr" | "sats" | "sub" | "suba" | "subi" | "subq" | "subx" | "swbeg" | "tpf" | "tst"
+                | "wddata" | "wdebug"
+        )
+        || matches!(
+            base,
+            "fabs" | "facos" | "fadd" | "fasin" | "fatan" | "fatanh" | "fcmp" | "fcos" | "fcosh"
+                | "fdabs" | "fdadd" | "fddiv" | "fdiv" | "fdmove" | "fdmul" | "fdneg" | "fdsqrt"
+                | "fdsub" | "fetox" | "fetoxm1" | "fgetexp" | "fgetman" | "fint" | "fintrz" | "flog10"
+                | "flog2" | "flogn" | "flognp1" | "fmod" | "fmove" | "fmovecr" | "fmovem" | "fmul"
+                | "fneg" | "frem" | "fsabs" | "fsadd" | "fscale" | "fsdiv" | "fsgldiv" | "fsglmul"
+                | "fsin" | "fsincos" | "fsinh" | "fsmove" | "fsmul" | "fsneg" | "fsqrt" | "fssqrt"
+                | "fssub" | "fsub" | "ftan" | "ftanh" | "ftentox" | "ftst" | "ftwotox"
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::m68k::{arch, dis::decode};
+
+    // The instruction in every syntax: MIT, Motorola, asm68k, vasm.
+    fn all(bytes: &[u8]) -> [String; 4] {
+        let insn = decode(bytes, 0x200, arch::M68000).unwrap();
+        [Syntax::Mit, Syntax::Motorola, Syntax::Asm68k, Syntax::Vasm]
+            .map(|syntax| Formatter::new(syntax).format(&insn))
+    }
+
+    #[test]
+    fn postincrement() {
+        assert_eq!(
+            all(&[0x20, 0xC0]),
+            [
+                "movel %d0,%a0@+",
+                "move.l d0,(a0)+",
+                "move.l d0,(a0)+",
+                "move.l d0,(a0)+"
+            ]
+        );
+    }
+
+    #[test]
+    fn indexed() {
+        assert_eq!(
+            all(&[0x20, 0x30, 0x10, 0x08]),
+            [
+                "movel %a0@(8,%d1:w),%d0",
+                "move.l (8,a0,d1.w),d0",
+                "move.l 8(a0,d1.w),d0",
+                "move.l (8,a0,d1.w),d0",
+            ]
+        );
+    }
+
+    #[test]
+    fn stack_pointer() {
+        assert_eq!(
+            all(&[0x2F, 0x00]),
+            [
+                "movel %d0,%a7@-",
+                "move.l d0,-(a7)",
+                "move.l d0,-(sp)",
+                "move.l d0,-(sp)"
+            ]
+        );
+    }
+
+    #[test]
+    fn hex_prefix() {
+        assert_eq!(
+            all(&[0x20, 0x39, 0x00, 0xFF, 0x00, 0x10]),
+            [
+                "movel 0xff0010,%d0",
+                "move.l ($ff0010).l,d0",
+                "move.l ($ff0010).l,d0",
+                "move.l ($ff0010).l,d0",
+            ]
+        );
+        assert_eq!(
+            all(&[0x61, 0x00, 0x00, 0x1C]),
+            ["bsrw 0x21e", "bsr.w $21e", "bsr.w $21e", "bsr.w $21e"]
+        );
+    }
+
+    #[test]
+    fn case_and_radix() {
+        let insn = decode(&[0x70, 0x1F], 0, arch::M68000).unwrap();
+        let mut formatter = Formatter::new(Syntax::Motorola);
+        assert_eq!(formatter.format(&insn), "moveq #31,d0");
+        formatter.radix = Radix::Hex;
+        assert_eq!(formatter.format(&insn), "moveq #$1f,d0");
+        formatter.case = Case::Upper;
+        assert_eq!(formatter.format(&insn), "MOVEQ #$1F,D0");
+        formatter.hex = HexStyle::ZeroX;
+        assert_eq!(formatter.format(&insn), "MOVEQ #0x1F,D0");
+
+        let insn = decode(&[0x20, 0x39, 0x00, 0xFF, 0x00, 0x10], 0, arch::M68000).unwrap();
+        assert_eq!(formatter.format(&insn), "MOVE.L (0xFF0010).L,D0");
+    }
+}
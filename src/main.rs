@@ -1,7 +1,31 @@
-mod m68k;
-mod md;
+use genesis_rs::md::rom::RomError;
+use genesis_rs::{m68k, md};
 
-fn main() -> Result<(), &'static str> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // genesis-rs --dat <dat> <rom> looks a dump up in a DAT file.
+    if let [_, flag, dat, input] = &std::env::args().collect::<Vec<_>>()[..] {
+        if flag == "--dat" {
+            let dat = md::dat::Dat::load(dat)?;
+            let rom = md::rom::load_from_file(input)?;
+            match rom.identify(&dat) {
+                Some(found) => println!("{}", found),
+                None => println!("{}: not in {}, bad or modified dump", rom.hashes(), dat.name),
+            }
+            return Ok(());
+        }
+    }
+    // genesis-rs <input> <output> converts between formats, picking the
+    // output format from its extension.
+    if let [_, input, output] = &std::env::args().collect::<Vec<_>>()[..] {
+        let rom = md::rom::load_from_file(input)?;
+        let extension = std::path::Path::new(output)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default();
+        let format = md::rom_fmt::Format::from_extension(extension)
+            .ok_or_else(|| RomError::UnknownFormat(extension.to_string()))?;
+        return Ok(rom.save(output, format)?);
+    }
     println!("Registers:");
     for register in m68k::cpu::REGISTER_NAMES {
         println!("{}", register)
@@ -10,17 +34,52 @@ fn main() -> Result<(), &'static str> {
     for code in m68k::OP_CODES {
         println!("{}", code)
     }
-    let rom = md::rom::load_from_file("sonic.md").ok_or("could not load rom")?;
+    let rom = md::rom::load_from_file("sonic.md")?;
 
-    println!("ROM: {}, FORMAT: {}", "sonic.md", rom.format);
+    println!("ROM: sonic.md, FORMAT: {}", rom.format);
 
     println!("Console: {}", rom.console());
-    println!("Copyright: {}", rom.copyright());
-    println!("Title: {}", rom.title());
-    println!("Overseas: {}", rom.name_overseas());
-    println!("Product type: {}", rom.product_type());
-    println!("Product code: {}", rom.product_code());
-    println!("Product type: {}", rom.checksum());
-    println!("Valid checksum: {:?}", rom.valid_checksum());
+    let header = rom.header();
+    println!("Console string: {}", header.console);
+    println!("Copyright: {}", header.copyright);
+    println!("Title: {}", header.title);
+    println!("Overseas: {}", header.overseas_title);
+    println!("Product type: {}", header.product_type);
+    println!("Product code: {}", header.product_code);
+    println!("Checksum: {:04X}", header.checksum);
+    let devices: Vec<String> = header.devices().iter().map(|d| d.to_string()).collect();
+    println!("Devices: {}", devices.join(", "));
+    println!("SRAM: {}", header.sram_info());
+    if let Some(modem) = header.modem_info() {
+        println!("Modem: {}", modem);
+    }
+    println!("Memo: {}", header.memo);
+    println!("Region: {} ({})", header.region, header.regions());
+    println!(
+        "Size: {:X} bytes, {:X} real, {:X} in header",
+        rom.data().len(),
+        rom.real_size(),
+        rom.header_size()
+    );
+    let checksum = rom.compute_checksum(md::checksum::ChecksumMode::Auto);
+    println!(
+        "Computed checksum: {:04X} over {:X?}, valid: {}",
+        checksum.computed,
+        checksum.range,
+        checksum.is_valid()
+    );
+    if let Some(boot) = rom.mars_boot() {
+        println!(
+            "32X boot: {} v{}, {:X} bytes from ${:X} to ${:08X}, master ${:08X}, slave ${:08X}",
+            boot.module_name,
+            boot.version,
+            boot.size,
+            boot.source,
+            boot.destination,
+            boot.master_entry,
+            boot.slave_entry
+        );
+    }
+    print!("Vectors:\n{}", rom.vectors());
     Ok(())
 }
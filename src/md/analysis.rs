@@ -0,0 +1,317 @@
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use crate::m68k::arch;
+use crate::m68k::dis::{decode, Instruction};
+use crate::m68k::operand::{EffectiveAddress, Operand};
+
+use super::mapper::Mapper;
+use super::rom::Rom;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ByteKind {
+    Unknown,
+    Code,
+    Data,
+}
+
+// A run of bytes of the same kind.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub range: Range<u32>,
+    pub kind: ByteKind,
+}
+
+// The result of code discovery: what every byte of the ROM is believed to be.
+// Instructions and labels are 68k addresses; byte kinds are kept per file
+// offset, since a mapper can show one byte at several addresses.
+#[derive(Clone, Debug)]
+pub struct CodeMap {
+    kinds: Vec<ByteKind>,
+    instructions: BTreeSet<u32>,
+    labels: BTreeSet<u32>,
+    mapper: Mapper,
+}
+
+impl CodeMap {
+    pub fn kind(&self, address: u32) -> ByteKind {
+        self.mapper
+            .translate(address)
+            .map_or(ByteKind::Unknown, |offset| self.kind_at(offset))
+    }
+    pub fn kind_at(&self, offset: usize) -> ByteKind {
+        self.kinds.get(offset).copied().unwrap_or(ByteKind::Unknown)
+    }
+    pub fn mapper(&self) -> &Mapper {
+        &self.mapper
+    }
+    // Addresses of the first byte of every instruction found.
+    pub fn instructions(&self) -> impl Iterator<Item = u32> + '_ {
+        self.instructions.iter().copied()
+    }
+    pub fn is_instruction(&self, address: u32) -> bool {
+        self.instructions.contains(&address)
+    }
+    // Vector, branch and call targets, plus addresses code reads data from.
+    pub fn labels(&self) -> impl Iterator<Item = u32> + '_ {
+        self.labels.iter().copied()
+    }
+    // Runs of file offsets.
+    pub fn spans(&self) -> Vec<Span> {
+        let mut spans: Vec<Span> = Vec::new();
+        for (address, &kind) in self.kinds.iter().enumerate() {
+            match spans.last_mut() {
+                Some(span) if span.kind == kind => span.range.end += 1,
+                _ => spans.push(Span {
+                    range: address as u32..address as u32 + 1,
+                    kind,
+                }),
+            }
+        }
+        spans
+    }
+    pub fn count(&self, kind: ByteKind) -> usize {
+        self.kinds.iter().filter(|&&k| k == kind).count()
+    }
+}
+
+// How an instruction passes control on.
+enum Flow {
+    Next,
+    // Branches that may or may not be taken, and subroutine calls, continue
+    // with the next instruction too.
+    Fork(Option<u32>),
+    Jump(Option<u32>),
+    Stop,
+}
+
+// The vector table and header, 0x000-0x1FF.
+const HEADER_END: u32 = 0x200;
+// The longest 68000 instruction.
+const MAX_INSTRUCTION: usize = 10;
+
+// Follows control flow from the reset and exception vectors, decoding as a
+// 68000. Anything reached is code; the vector table, header and operands read
+// by code are data; everything else is unknown. Addresses go through the
+// ROM's mapper in its power on state, so banked ROMs are read as the console
+// sees them after reset. On a 32X that includes the adapter's windows.
+pub fn discover(rom: &Rom) -> CodeMap {
+    let mapper = rom.mapper();
+    let size = rom.data().len();
+    let mut map = CodeMap {
+        kinds: vec![ByteKind::Unknown; size],
+        instructions: BTreeSet::new(),
+        labels: BTreeSet::new(),
+        mapper: mapper.clone(),
+    };
+    map.kinds[..size.min(HEADER_END as usize)].fill(ByteKind::Data);
+
+    // Vector 0 is the initial stack pointer, the rest are code addresses.
+    let mut pending: Vec<u32> = rom.vectors().iter().skip(1).collect();
+    pending.retain(|&address| is_code_address(&mapper, address));
+    map.labels.extend(pending.iter().copied());
+    // Data marks are applied once all code is known, so a wrong guess about
+    // an operand never blocks a code path.
+    let mut references = Vec::new();
+
+    while let Some(mut address) = pending.pop() {
+        while is_code_address(&mapper, address) && !map.instructions.contains(&address) {
+            let bytes = rom.read(&mapper, address, MAX_INSTRUCTION).unwrap_or_default();
+            let insn = match decode(bytes, address, arch::M68000) {
+                Ok(insn) => insn,
+                Err(_) => break,
+            };
+            // read() only returns bytes contiguous in the file.
+            let offset = mapper.translate(address).unwrap();
+            let range = offset..offset + insn.length;
+            // Never let a path run into bytes already claimed by something
+            // else; one of the two interpretations is wrong.
+            if map.kinds[range.clone()].iter().any(|&kind| kind != ByteKind::Unknown) {
+                break;
+            }
+            map.kinds[range].fill(ByteKind::Code);
+            map.instructions.insert(address);
+            references.extend(data_references(&insn));
+
+            let next = address + insn.length as u32;
+            match flow(&insn) {
+                Flow::Next => address = next,
+                Flow::Fork(target) => {
+                    if let Some(target) = target.filter(|&t| is_code_address(&mapper, t)) {
+                        map.labels.insert(target);
+                        pending.push(target);
+                    }
+                    address = next;
+                }
+                Flow::Jump(target) => match target.filter(|&t| is_code_address(&mapper, t)) {
+                    Some(target) => {
+                        map.labels.insert(target);
+                        address = target;
+                    }
+                    None => break,
+                },
+                Flow::Stop => break,
+            }
+        }
+    }
+    for (address, len) in references {
+        if let Some(offset) = mapper.translate(address) {
+            map.labels.insert(address);
+            let end = (offset + len).min(size);
+            for kind in &mut map.kinds[offset..end] {
+                if *kind == ByteKind::Unknown {
+                    *kind = ByteKind::Data;
+                }
+            }
+        }
+    }
+    map
+}
+
+// Instructions must be word aligned and lie in ROM past the header.
+fn is_code_address(mapper: &Mapper, address: u32) -> bool {
+    address.is_multiple_of(2)
+        && mapper
+            .translate(address)
+            .is_some_and(|offset| offset >= HEADER_END as usize)
+}
+
+fn flow(insn: &Instruction) -> Flow {
+    let name = insn.mnemonic();
+    let branch = insn.operands.iter().find_map(|operand| match operand {
+        Operand::Branch(target) => Some(*target),
+        _ => None,
+    });
+    // jmp and jsr only have a known destination for absolute and PC
+    // relative operands.
+    let destination = || match insn.operands.first() {
+        Some(Operand::Ea(EffectiveAddress::AbsoluteShort(addr))) => Some(*addr as i32 as u32),
+        Some(Operand::Ea(EffectiveAddress::AbsoluteLong(addr))) => Some(*addr),
+        Some(Operand::Ea(EffectiveAddress::PcDisplacement { target, .. })) => Some(*target),
+        _ => None,
+    };
+    match name {
+        "rts" | "rte" | "rtr" | "rtd" | "illegal" => Flow::Stop,
+        "jmp" => Flow::Jump(destination()),
+        "jsr" => Flow::Fork(destination()),
+        "bras" | "braw" | "bral" => Flow::Jump(branch),
+        // Conditional branches, bsr and dbcc.
+        _ if branch.is_some() => Flow::Fork(branch),
+        _ => Flow::Next,
+    }
+}
+
+// Addresses and sizes a non-branching instruction reads or writes through
+// absolute or PC relative operands, using the size from the mnemonic.
+fn data_references(insn: &Instruction) -> Vec<(u32, usize)> {
+    let name = insn.mnemonic();
+    if matches!(name, "jmp" | "jsr") {
+        return Vec::new();
+    }
+    let size = match name.chars().last() {
+        Some('b') => 1,
+        Some('w') => 2,
+        Some('l') => 4,
+        // lea and pea only take the address.
+        _ => 0,
+    };
+    insn.operands
+        .iter()
+        .filter_map(|operand| match operand {
+            Operand::Ea(EffectiveAddress::AbsoluteShort(addr)) => Some(*addr as i32 as u32),
+            Operand::Ea(EffectiveAddress::AbsoluteLong(addr)) => Some(*addr),
+            Operand::Ea(EffectiveAddress::PcDisplacement { target, .. }) => Some(*target),
+            _ => None,
+        })
+        .map(|address| (address, size))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md::rom_fmt::Format;
+
+    // Reset goes to 0x200; the other vectors are zero and point nowhere.
+    fn rom_with_code(code: &[(u32, &[u8])]) -> Rom {
+        let mut data = vec![0xFF; 0x1000];
+        data[..8].copy_from_slice(&[0x00, 0xFF, 0xFE, 0x00, 0x00, 0x00, 0x02, 0x00]);
+        data[8..0x100].fill(0);
+        data[0x100..0x104].copy_from_slice(b"SEGA");
+        for &(address, bytes) in code {
+            let at = address as usize;
+            data[at..at + bytes.len()].copy_from_slice(bytes);
+        }
+        Rom::from_bytes(data, Format::Bin).unwrap()
+    }
+
+    #[test]
+    fn follows_control_flow() {
+        let rom = rom_with_code(&[
+            (0x200, &[0x4E, 0x71]),                         // nop
+            (0x202, &[0x61, 0x00, 0x00, 0x1C]),             // bsr.w 0x220
+            (0x206, &[0x66, 0x04]),                         // bne.s 0x20C
+            (0x208, &[0x60, 0x26]),                         // bra.s 0x230
+            (0x20C, &[0x51, 0xC8, 0xFF, 0xFE]),             // dbf d0,0x20C
+            (0x210, &[0x22, 0x38, 0x03, 0x00]),             // move.l (0x300).w,d1
+            (0x214, &[0x4E, 0xF9, 0x00, 0x00, 0x02, 0x40]), // jmp 0x240
+            (0x220, &[0x4E, 0x75]),                         // rts
+            (0x230, &[0x4E, 0x73]),                         // rte
+            (0x240, &[0x4E, 0x75]),                         // rts
+        ]);
+        let map = discover(&rom);
+        let instructions: Vec<u32> = map.instructions().collect();
+        assert_eq!(
+            instructions,
+            [0x200, 0x202, 0x206, 0x208, 0x20C, 0x210, 0x214, 0x220, 0x230, 0x240]
+        );
+        for label in [0x200, 0x220, 0x20C, 0x230, 0x240, 0x300] {
+            assert!(map.labels().any(|l| l == label), "no label at {:#x}", label);
+        }
+        // Nothing runs on after bra, jmp, rts or rte.
+        for address in [0x20A, 0x21A, 0x222, 0x232, 0x242] {
+            assert_eq!(map.kind(address), ByteKind::Unknown, "{:#x}", address);
+        }
+        assert_eq!(map.kind(0x219), ByteKind::Code);
+    }
+
+    #[test]
+    fn classifies_bytes() {
+        let rom = rom_with_code(&[
+            (0x200, &[0x22, 0x38, 0x03, 0x00]), // move.l (0x300).w,d1
+            (0x204, &[0x4E, 0x75]),             // rts
+        ]);
+        let map = discover(&rom);
+        assert_eq!(map.kind(0x000), ByteKind::Data);
+        assert_eq!(map.kind(0x1FF), ByteKind::Data);
+        assert_eq!(map.kind(0x200), ByteKind::Code);
+        assert_eq!(map.kind(0x300), ByteKind::Data);
+        assert_eq!(map.kind(0x303), ByteKind::Data);
+        assert_eq!(map.kind(0x304), ByteKind::Unknown);
+        assert_eq!(map.kind(0x10_0000), ByteKind::Unknown);
+        assert_eq!(map.count(ByteKind::Code), 6);
+        assert_eq!(map.count(ByteKind::Data), 0x204);
+        let spans = map.spans();
+        assert_eq!(
+            spans[0],
+            Span {
+                range: 0..0x200,
+                kind: ByteKind::Data
+            }
+        );
+        assert_eq!(
+            spans[1],
+            Span {
+                range: 0x200..0x206,
+                kind: ByteKind::Code
+            }
+        );
+        assert_eq!(
+            spans[2],
+            Span {
+                range: 0x206..0x300,
+                kind: ByteKind::Unknown
+            }
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use super::error::RomError;
+use super::hash::crc32;
+use super::patch_util::{check_crc, split_footer, write_number, Reader};
+use super::rom::Rom;
+
+// beat patches: "BPS1", source, target and metadata sizes, copy actions, then
+// the source, target and patch CRC32s.
+// Ref: https://www.romhacking.net/documents/746/
+const MAGIC: &[u8] = b"BPS1";
+
+const SOURCE_READ: u64 = 0;
+const TARGET_READ: u64 = 1;
+const SOURCE_COPY: u64 = 2;
+const TARGET_COPY: u64 = 3;
+
+// Applies a BPS patch to a ROM. On error the ROM is left unchanged.
+pub fn apply(rom: &mut Rom, patch: &[u8]) -> Result<(), RomError> {
+    rom.replace_data(apply_to(rom.data(), patch)?)
+}
+
+// Produces the patched bytes, checking the patch, source and target CRC32s.
+pub fn apply_to(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, RomError> {
+    let (body, crcs) = split_footer(patch, MAGIC)?;
+    check_crc("patch", crcs[2], crc32(&patch[..patch.len() - 4]))?;
+    let mut reader = Reader::new(&body[MAGIC.len()..]);
+    let source_size = reader.size()?;
+    let target_size = reader.size()?;
+    let metadata_size = reader.number()? as usize;
+    reader.take(metadata_size)?;
+    check_crc("source", crcs[0], crc32(source))?;
+    if source.len() != source_size {
+        return Err(RomError::BadPatch("source size does not match"));
+    }
+
+    let mut target = Vec::new();
+    let mut source_offset = 0usize;
+    let mut target_offset = 0usize;
+    let bad = |reason| RomError::BadPatch(reason);
+    while !reader.is_empty() {
+        let action = reader.number()?;
+        let len = (action >> 2) as usize + 1;
+        if target.len() + len > target_size {
+            return Err(bad("action writes past the target size"));
+        }
+        match action & 3 {
+            SOURCE_READ => {
+                let at = target.len();
+                target.extend(source.get(at..at + len).ok_or(bad("read past the source"))?);
+            }
+            TARGET_READ => target.extend(reader.take(len)?),
+            SOURCE_COPY => {
+                source_offset = relative(source_offset, reader.number()?)
+                    .ok_or(bad("copy before the source"))?;
+                let bytes = source
+                    .get(source_offset..source_offset + len)
+                    .ok_or(bad("copy past the source"))?;
+                target.extend(bytes);
+                source_offset += len;
+            }
+            _ => {
+                target_offset = relative(target_offset, reader.number()?)
+                    .ok_or(bad("copy before the target"))?;
+                // The copy may overlap what it writes, so go a byte at a time.
+                for _ in 0..len {
+                    let byte = *target.get(target_offset).ok_or(bad("copy past the target"))?;
+                    target.push(byte);
+                    target_offset += 1;
+                }
+            }
+        }
+    }
+    if target.len() != target_size {
+        return Err(bad("actions do not fill the target"));
+    }
+    check_crc("target", crcs[1], crc32(&target))?;
+    Ok(target)
+}
+
+// Builds a BPS patch turning `source` into `target`.
+pub fn create(source: &Rom, target: &Rom) -> Vec<u8> {
+    create_from(source.data(), target.data())
+}
+
+// Builds a BPS patch from raw bytes. Unchanged runs are read from the source,
+// runs of one byte are copied from the target just written, and everything
+// else is stored literally. Moved data is not searched for.
+pub fn create_from(source: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut patch = MAGIC.to_vec();
+    write_number(&mut patch, source.len() as u64);
+    write_number(&mut patch, target.len() as u64);
+    write_number(&mut patch, 0);
+
+    let same = |i: usize| (i..target.len()).take_while(|&j| source.get(j) == Some(&target[j])).count();
+    let repeat = |i: usize| match i.checked_sub(1) {
+        Some(previous) => target[i..].iter().take_while(|&&b| b == target[previous]).count(),
+        None => 0,
+    };
+    let mut target_offset = 0usize;
+    let mut literal = 0;
+    let mut i = 0;
+    let action = |patch: &mut Vec<u8>, kind: u64, len: usize| {
+        write_number(patch, ((len as u64 - 1) << 2) | kind);
+    };
+    while i < target.len() {
+        let (same, repeat) = (same(i), repeat(i));
+        if same < 4 && repeat < 4 {
+            i += 1;
+            continue;
+        }
+        if literal < i {
+            action(&mut patch, TARGET_READ, i - literal);
+            patch.extend(&target[literal..i]);
+        }
+        if same >= repeat {
+            action(&mut patch, SOURCE_READ, same);
+            i += same;
+        } else {
+            action(&mut patch, TARGET_COPY, repeat);
+            let from = i - 1;
+            let delta = from as i64 - target_offset as i64;
+            write_number(&mut patch, (delta.unsigned_abs() << 1) | (delta < 0) as u64);
+            target_offset = from + repeat;
+            i += repeat;
+        }
+        literal = i;
+    }
+    if literal < target.len() {
+        action(&mut patch, TARGET_READ, target.len() - literal);
+        patch.extend(&target[literal..]);
+    }
+    patch.extend(crc32(source).to_le_bytes());
+    patch.extend(crc32(target).to_le_bytes());
+    patch.extend(crc32(&patch).to_le_bytes());
+    patch
+}
+
+// Moves an offset by a signed delta: the low bit is the sign, the rest the
+// magnitude.
+fn relative(offset: usize, delta: u64) -> Option<usize> {
+    let magnitude = usize::try_from(delta >> 1).ok()?;
+    if delta & 1 != 0 {
+        offset.checked_sub(magnitude)
+    } else {
+        offset.checked_add(magnitude)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source() -> Vec<u8> {
+        (0..0x2000).map(|i| (i * 31 + i / 97) as u8).collect()
+    }
+
+    // A patch with the given header numbers, no actions and valid CRC32s.
+    fn empty_patch(source: &[u8], target_size: u64) -> Vec<u8> {
+        let mut patch = MAGIC.to_vec();
+        for number in [source.len() as u64, target_size, 0] {
+            write_number(&mut patch, number);
+        }
+        patch.extend(crc32(source).to_le_bytes());
+        patch.extend(0u32.to_le_bytes());
+        patch.extend(crc32(&patch).to_le_bytes());
+        patch
+    }
+
+    #[test]
+    fn create_and_apply() {
+        let source = source();
+        let mut target = source.clone();
+        target[0x100..0x180].fill(0x77);
+        target[0x400] ^= 1;
+        target.extend(b"appended");
+        for target in [&target[..], &target[..0x1000], &[]] {
+            let patch = create_from(&source, target);
+            assert_eq!(apply_to(&source, &patch).unwrap(), target);
+        }
+    }
+
+    #[test]
+    fn wrong_source_is_rejected() {
+        let source = source();
+        let patch = create_from(&source, &source[..0x100]);
+        let mut other = source.clone();
+        other[0] ^= 1;
+        assert!(matches!(
+            apply_to(&other, &patch),
+            Err(RomError::CrcMismatch { what: "source", .. })
+        ));
+        let mut damaged = patch.clone();
+        damaged[6] ^= 1;
+        assert!(matches!(
+            apply_to(&source, &damaged),
+            Err(RomError::CrcMismatch { what: "patch", .. })
+        ));
+    }
+
+    #[test]
+    fn malformed_sizes() {
+        let source = source();
+        let huge = empty_patch(&source, 1 << 40);
+        assert!(matches!(
+            apply_to(&source, &huge),
+            Err(RomError::BadPatch("size too large"))
+        ));
+        let unfilled = empty_patch(&source, 0x10);
+        assert!(matches!(
+            apply_to(&source, &unfilled),
+            Err(RomError::BadPatch("actions do not fill the target"))
+        ));
+        assert!(matches!(
+            apply_to(&source, b"BPS1"),
+            Err(RomError::BadPatch("truncated patch"))
+        ));
+    }
+}
@@ -0,0 +1,102 @@
+use std::ops::Range;
+
+use super::header::HEADER_END;
+
+// Which bytes the header checksum covers. It always starts after the header.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChecksumMode {
+    // Up to and including the ROM end address in the header, as the boot
+    // code of most games checks it.
+    HeaderEnd,
+    // To the end of the file, which differs from HeaderEnd for padded or
+    // overdumped files.
+    WholeFile,
+    // Whichever of the two matches the stored value, preferring HeaderEnd.
+    Auto,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checksum {
+    pub computed: u16,
+    pub stored: u16,
+    // File offsets that were summed.
+    pub range: Range<usize>,
+}
+
+impl Checksum {
+    pub fn is_valid(&self) -> bool {
+        self.computed == self.stored
+    }
+}
+
+// The 16 bit sum of big endian words. A trailing odd byte counts as the
+// high byte of a word whose low byte is zero.
+pub fn sum_words(data: &[u8]) -> u16 {
+    data.chunks(2).fold(0u16, |sum, word| {
+        let word = u16::from_be_bytes([word[0], word.get(1).copied().unwrap_or(0)]);
+        sum.wrapping_add(word)
+    })
+}
+
+// Computes the checksum of `data`, whose header claims the ROM ends at
+// `rom_end` and stores `stored`. A header end past the file or before the
+// header is clamped to the file.
+pub fn compute(data: &[u8], rom_end: u32, stored: u16, mode: ChecksumMode) -> Checksum {
+    let start = HEADER_END.min(data.len());
+    let header_end = (rom_end as usize).saturating_add(1).clamp(start, data.len());
+    let sum = |end: usize| Checksum {
+        computed: sum_words(&data[start..end]),
+        stored,
+        range: start..end,
+    };
+    match mode {
+        ChecksumMode::HeaderEnd => sum(header_end),
+        ChecksumMode::WholeFile => sum(data.len()),
+        ChecksumMode::Auto => {
+            let checksum = sum(header_end);
+            if checksum.is_valid() || header_end == data.len() {
+                return checksum;
+            }
+            let whole = sum(data.len());
+            if whole.is_valid() {
+                whole
+            } else {
+                checksum
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn odd_byte_is_a_high_byte() {
+        assert_eq!(sum_words(&[0x12, 0x34, 0x56]), 0x6834);
+        assert_eq!(sum_words(&[0xFF, 0xFF, 0x00, 0x02]), 0x0001);
+    }
+
+    #[test]
+    fn odd_length_rom() {
+        let mut data = vec![0; HEADER_END];
+        data.extend([0x12, 0x34, 0x56]);
+        let whole = compute(&data, 0x202, 0x6834, ChecksumMode::HeaderEnd);
+        assert_eq!(whole.range, HEADER_END..HEADER_END + 3);
+        assert!(whole.is_valid());
+
+        // A header end one byte short leaves the odd byte out, which Auto
+        // notices from the stored value.
+        let short = compute(&data, 0x201, 0x6834, ChecksumMode::HeaderEnd);
+        assert_eq!(short.computed, 0x1234);
+        assert_eq!(compute(&data, 0x201, 0x6834, ChecksumMode::Auto), whole);
+    }
+
+    #[test]
+    fn header_end_is_clamped_to_the_file() {
+        let data = vec![0x01; HEADER_END + 4];
+        let checksum = compute(&data, 0xFFFF_FFFF, 0, ChecksumMode::HeaderEnd);
+        assert_eq!(checksum.range, HEADER_END..data.len());
+        assert_eq!(checksum.computed, 0x0202);
+    }
+}
@@ -0,0 +1,239 @@
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+
+use super::header::{Header, Text};
+
+// The system an image is for, from the console string at 0x100 and, for
+// Mega CD boot images, the disc signature at 0.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Console {
+    Genesis,
+    Sega32X,
+    Pico,
+    MegaCd,
+    Unknown,
+}
+
+impl Console {
+    pub fn detect(data: &[u8], header: &Header) -> Self {
+        let console = header.console.text.to_ascii_uppercase();
+        if data.starts_with(b"SEGADISCSYSTEM")
+            || ["MEGA CD", "MEGA-CD", "SEGA CD"].iter().any(|s| console.contains(s))
+        {
+            Self::MegaCd
+        } else if console.contains("32X") {
+            Self::Sega32X
+        } else if console.contains("PICO") {
+            Self::Pico
+        } else if console.contains("SEGA") {
+            Self::Genesis
+        } else {
+            Self::Unknown
+        }
+    }
+
+    // The 68k address space as this system lays it out. Unknown images get
+    // the Genesis map.
+    pub fn memory_map(&self) -> &'static [MemoryArea] {
+        match self {
+            Self::Genesis | Self::Unknown => &GENESIS_MAP,
+            Self::Sega32X => &SEGA_32X_MAP,
+            Self::Pico => &PICO_MAP,
+            Self::MegaCd => &MEGA_CD_MAP,
+        }
+    }
+    // The named area an address falls in.
+    pub fn area(&self, address: u32) -> Option<&'static MemoryArea> {
+        self.memory_map().iter().find(|area| area.range.contains(&address))
+    }
+}
+
+impl Display for Console {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Self::Genesis => "Genesis / Mega Drive",
+            Self::Sega32X => "32X",
+            Self::Pico => "Pico",
+            Self::MegaCd => "Mega CD",
+            Self::Unknown => "unknown",
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct MemoryArea {
+    pub name: &'static str,
+    pub range: RangeInclusive<u32>,
+    // Whether the area shows bytes of the ROM image.
+    pub rom: bool,
+}
+
+const fn area(name: &'static str, start: u32, end: u32, rom: bool) -> MemoryArea {
+    MemoryArea {
+        name,
+        range: start..=end,
+        rom,
+    }
+}
+
+// Ref: https://segaretro.org/Sega_Mega_Drive/Memory_map
+static GENESIS_MAP: [MemoryArea; 7] = [
+    area("Cartridge ROM", 0x000000, 0x3FFFFF, true),
+    area("Z80 address space", 0xA00000, 0xA0FFFF, false),
+    area("I/O registers", 0xA10000, 0xA1001F, false),
+    area("Z80 bus control", 0xA11100, 0xA112FF, false),
+    area("Mapper registers", 0xA130F0, 0xA130FF, false),
+    area("VDP", 0xC00000, 0xC0001F, false),
+    area("Work RAM", 0xFF0000, 0xFFFFFF, false),
+];
+
+// With the adapter enabled. Before that the cartridge is at 0 as usual.
+// Ref: https://segaretro.org/Sega_32X/Technical_specifications
+static SEGA_32X_MAP: [MemoryArea; 11] = [
+    area("32X vector ROM", 0x000000, 0x0000FF, false),
+    area("Cartridge ROM (adapter off)", 0x000100, 0x3FFFFF, true),
+    area("32X frame buffer", 0x840000, 0x85FFFF, false),
+    area("32X frame buffer overwrite", 0x860000, 0x87FFFF, false),
+    area("Cartridge ROM, first 512 KB", 0x880000, 0x8FFFFF, true),
+    area("Cartridge ROM, banked", 0x900000, 0x9FFFFF, true),
+    area("Z80 address space", 0xA00000, 0xA0FFFF, false),
+    area("I/O registers", 0xA10000, 0xA1001F, false),
+    area("32X system registers", 0xA15100, 0xA153FF, false),
+    area("VDP", 0xC00000, 0xC0001F, false),
+    area("Work RAM", 0xFF0000, 0xFFFFFF, false),
+];
+
+// Ref: https://segaretro.org/Sega_Pico/Technical_specifications
+static PICO_MAP: [MemoryArea; 4] = [
+    area("Cartridge ROM", 0x000000, 0x3FFFFF, true),
+    area("Pico I/O and ADPCM", 0x800000, 0x80001F, false),
+    area("VDP", 0xC00000, 0xC0001F, false),
+    area("Work RAM", 0xFF0000, 0xFFFFFF, false),
+];
+
+// The main CPU's view, with the boot ROM at 0 rather than a cartridge.
+// Ref: https://segaretro.org/Mega-CD/Technical_specifications
+static MEGA_CD_MAP: [MemoryArea; 8] = [
+    area("Boot ROM", 0x000000, 0x01FFFF, false),
+    area("Program RAM window", 0x020000, 0x03FFFF, false),
+    area("Word RAM", 0x200000, 0x23FFFF, false),
+    area("Z80 address space", 0xA00000, 0xA0FFFF, false),
+    area("I/O registers", 0xA10000, 0xA1001F, false),
+    area("Mega CD gate array", 0xA12000, 0xA1202F, false),
+    area("VDP", 0xC00000, 0xC0001F, false),
+    area("Work RAM", 0xFF0000, 0xFFFFFF, false),
+];
+
+// The SH-2 boot table of 32X cartridges, after the 68k vectors and header.
+// The adapter copies `size` bytes from ROM offset `source` to SDRAM at
+// `destination`, then starts both SH-2s.
+// Ref: https://segaretro.org/Sega_32X/ROM_header
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MarsBoot {
+    pub module_name: Text,
+    pub version: u32,
+    pub source: u32,
+    pub destination: u32,
+    pub size: u32,
+    pub master_entry: u32,
+    pub slave_entry: u32,
+    pub master_vbr: u32,
+    pub slave_vbr: u32,
+}
+
+pub const MARS_BOOT_START: usize = 0x3C0;
+pub const MARS_BOOT_END: usize = 0x3F0;
+
+impl MarsBoot {
+    // Reads the table at 0x3C0. None if the image is too short to hold it.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let table = data.get(MARS_BOOT_START..MARS_BOOT_END)?;
+        let long = |at: usize| u32::from_be_bytes(table[at..at + 4].try_into().unwrap());
+        Some(Self {
+            module_name: Text::new(&table[..0x10]),
+            version: long(0x10),
+            source: long(0x14),
+            destination: long(0x18),
+            size: long(0x1C),
+            master_entry: long(0x20),
+            slave_entry: long(0x24),
+            master_vbr: long(0x28),
+            slave_vbr: long(0x2C),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(console: &[u8; 16]) -> Vec<u8> {
+        let mut data = vec![0; 0x400];
+        data[0x100..0x110].copy_from_slice(console);
+        data
+    }
+
+    fn detect(data: &[u8]) -> Console {
+        Console::detect(data, &Header::parse(data).unwrap())
+    }
+
+    #[test]
+    fn consoles() {
+        assert_eq!(detect(&image(b"SEGA MEGA DRIVE ")), Console::Genesis);
+        assert_eq!(detect(&image(b"SEGA GENESIS    ")), Console::Genesis);
+        assert_eq!(detect(&image(b"SEGA 32X        ")), Console::Sega32X);
+        assert_eq!(detect(&image(b"SEGA MEGA CD    ")), Console::MegaCd);
+        assert_eq!(detect(&image(b"sega cd         ")), Console::MegaCd);
+        assert_eq!(detect(&image(b"SEGA PICO       ")), Console::Pico);
+        assert_eq!(detect(&image(b"                ")), Console::Unknown);
+
+        // Boot images are found by the disc signature too.
+        let mut boot = image(b"SEGA MEGA DRIVE ");
+        boot[..14].copy_from_slice(b"SEGADISCSYSTEM");
+        assert_eq!(detect(&boot), Console::MegaCd);
+    }
+
+    #[test]
+    fn memory_maps() {
+        assert_eq!(
+            Console::Genesis.area(0xA1_30F3).unwrap().name,
+            "Mapper registers"
+        );
+        assert!(Console::Sega32X.area(0x90_0000).unwrap().rom);
+        assert!(!Console::Sega32X.area(0x00_0000).unwrap().rom);
+        assert!(!Console::MegaCd.area(0x00_0200).unwrap().rom);
+        assert!(Console::Unknown.area(0x80_0000).is_none());
+    }
+
+    #[test]
+    fn mars_boot_fields() {
+        let mut data = image(b"SEGA 32X        ");
+        data[MARS_BOOT_START..MARS_BOOT_START + 0x10].copy_from_slice(b"MARS CHECK MODE ");
+        let longs: [u32; 8] = [
+            0x0000_0001,
+            0x0000_0400,
+            0x0600_0000,
+            0x0002_0000,
+            0x0600_0120,
+            0x0600_0124,
+            0x0600_0000,
+            0x0600_0100,
+        ];
+        for (i, long) in longs.iter().enumerate() {
+            let at = MARS_BOOT_START + 0x10 + i * 4;
+            data[at..at + 4].copy_from_slice(&long.to_be_bytes());
+        }
+        let boot = MarsBoot::parse(&data).unwrap();
+        assert_eq!(boot.module_name.text, "MARS CHECK MODE");
+        assert_eq!(boot.version, 1);
+        assert_eq!(boot.source, 0x400);
+        assert_eq!(boot.destination, 0x0600_0000);
+        assert_eq!(boot.size, 0x2_0000);
+        assert_eq!(boot.master_entry, 0x0600_0120);
+        assert_eq!(boot.slave_entry, 0x0600_0124);
+        assert_eq!(boot.master_vbr, 0x0600_0000);
+        assert_eq!(boot.slave_vbr, 0x0600_0100);
+
+        assert!(MarsBoot::parse(&data[..MARS_BOOT_END - 1]).is_none());
+    }
+}
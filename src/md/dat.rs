@@ -0,0 +1,507 @@
+use std::fmt::{self, Display, Formatter};
+
+use super::hash::{from_hex, Hashes};
+
+// Dump sets such as No-Intro and Redump, in Logiqx XML or ClrMamePro format.
+// Ref: http://www.logiqx.com/DatFAQs/DatCreation.php
+#[derive(Clone, Debug, Default)]
+pub struct Dat {
+    pub name: String,
+    pub games: Vec<Game>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Game {
+    pub name: String,
+    pub description: String,
+    pub roms: Vec<DatRom>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DatRom {
+    pub name: String,
+    pub size: Option<usize>,
+    pub crc32: Option<u32>,
+    pub md5: Option<[u8; 16]>,
+    pub sha1: Option<[u8; 20]>,
+    pub status: Status,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Status {
+    #[default]
+    Good,
+    Verified,
+    BadDump,
+    NoDump,
+}
+
+impl Status {
+    fn parse(text: &str) -> Self {
+        match text {
+            "verified" => Self::Verified,
+            "baddump" => Self::BadDump,
+            "nodump" => Self::NoDump,
+            _ => Self::Good,
+        }
+    }
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", match self {
+            Self::Good => "good",
+            Self::Verified => "verified",
+            Self::BadDump => "bad dump",
+            Self::NoDump => "no dump",
+        })
+    }
+}
+
+// A DAT entry a dump was found to be.
+#[derive(Copy, Clone, Debug)]
+pub struct Match<'a> {
+    pub game: &'a Game,
+    pub rom: &'a DatRom,
+}
+
+impl Display for Match<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{} [{}]", self.game.name, self.rom.status)
+    }
+}
+
+#[derive(Debug)]
+pub enum DatError {
+    Io(std::io::Error),
+    Syntax { line: usize, message: &'static str },
+}
+
+impl Display for DatError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{}", error),
+            Self::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for DatError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for DatError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl Dat {
+    pub fn load(path: &str) -> Result<Self, DatError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+    // Parses either format, telling them apart by the first character.
+    pub fn parse(text: &str) -> Result<Self, DatError> {
+        if text.trim_start().starts_with('<') {
+            parse_xml(text)
+        } else {
+            parse_clrmamepro(text)
+        }
+    }
+    // Finds the entry a dump with these hashes is. Each entry is compared by
+    // the strongest digest it lists, and by size when it has one. None means
+    // the dump is unknown to the set: a bad dump, a hack, or just missing.
+    pub fn identify(&self, hashes: &Hashes) -> Option<Match<'_>> {
+        self.games.iter().find_map(|game| {
+            let rom = game.roms.iter().find(|rom| rom.matches(hashes))?;
+            Some(Match { game, rom })
+        })
+    }
+}
+
+impl DatRom {
+    pub fn matches(&self, hashes: &Hashes) -> bool {
+        if self.size.is_some_and(|size| size != hashes.size) {
+            return false;
+        }
+        match (self.sha1, self.md5, self.crc32) {
+            (Some(sha1), _, _) => sha1 == hashes.sha1,
+            (None, Some(md5), _) => md5 == hashes.md5,
+            (None, None, Some(crc32)) => crc32 == hashes.crc32,
+            (None, None, None) => false,
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        match key {
+            "name" => self.name = value.to_string(),
+            "size" => self.size = value.parse().ok(),
+            "crc" => self.crc32 = u32::from_str_radix(value, 16).ok(),
+            "md5" => self.md5 = from_hex(value),
+            "sha1" => self.sha1 = from_hex(value),
+            "status" | "flags" => self.status = Status::parse(value),
+            _ => {}
+        }
+    }
+}
+
+// Named and numeric (&#N; and &#xN;) references. Anything else that starts
+// with '&' is kept as it is.
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let entity = rest[1..].find(';').and_then(|end| {
+            let name = &rest[1..end + 1];
+            let c = match name {
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                "amp" => '&',
+                _ => {
+                    let code = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => name.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end + 2))
+        });
+        match entity {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+// Only the elements DATs use are understood: header/name, game or machine
+// with description, and rom.
+fn parse_xml(text: &str) -> Result<Dat, DatError> {
+    let mut dat = Dat::default();
+    let mut game: Option<Game> = None;
+    let mut path: Vec<String> = Vec::new();
+    let mut pos = 0;
+    // Lines are counted up to `counted` as the scan moves forward.
+    let (mut line, mut counted) = (1, 0);
+    while let Some(start) = text[pos..].find('<').map(|i| pos + i) {
+        line += text[counted..start].matches('\n').count();
+        counted = start;
+        let content = unescape(text[pos..start].trim());
+        if !content.is_empty() {
+            match (path.last().map(String::as_str), path.iter().rev().nth(1).map(String::as_str)) {
+                (Some("name"), Some("header")) => dat.name = content,
+                (Some("description"), _) => {
+                    if let Some(game) = &mut game {
+                        game.description = content;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let syntax = |message| DatError::Syntax {
+            line,
+            message,
+        };
+        // Comments, the XML declaration and the doctype.
+        if text[start..].starts_with("<!--") {
+            let end = text[start..].find("-->").ok_or_else(|| syntax("unterminated comment"))?;
+            pos = start + end + 3;
+            continue;
+        }
+        let end = start + text[start..].find('>').ok_or_else(|| syntax("unterminated tag"))?;
+        pos = end + 1;
+        let tag = &text[start + 1..end];
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim();
+            if path.pop().as_deref() != Some(name) {
+                return Err(syntax("mismatched closing tag"));
+            }
+            if matches!(name, "game" | "machine") {
+                dat.games.extend(game.take());
+            }
+            continue;
+        }
+        let closed = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let name = tag.split_whitespace().next().ok_or_else(|| syntax("empty tag"))?;
+        let attributes = parse_attributes(&tag[name.len()..]).ok_or_else(|| syntax("bad attribute"))?;
+        match name {
+            "game" | "machine" => {
+                let mut new = Game::default();
+                for (key, value) in &attributes {
+                    if key == "name" {
+                        new.name = value.clone();
+                    }
+                }
+                game = Some(new);
+            }
+            "rom" => {
+                let mut rom = DatRom::default();
+                for (key, value) in &attributes {
+                    rom.set(key, value);
+                }
+                game.as_mut().ok_or_else(|| syntax("rom outside a game"))?.roms.push(rom);
+            }
+            _ => {}
+        }
+        if !closed {
+            path.push(name.to_string());
+        }
+    }
+    Ok(dat)
+}
+
+fn parse_attributes(mut text: &str) -> Option<Vec<(String, String)>> {
+    let mut attributes = Vec::new();
+    loop {
+        text = text.trim_start();
+        if text.is_empty() {
+            return Some(attributes);
+        }
+        let (key, rest) = text.split_once('=')?;
+        let rest = rest.trim_start();
+        let quote = rest.chars().next().filter(|&c| c == '"' || c == '\'')?;
+        let (value, rest) = rest[1..].split_once(quote)?;
+        attributes.push((key.trim().to_string(), unescape(value)));
+        text = rest;
+    }
+}
+
+// A ClrMamePro value: a word or quoted string, or a parenthesised list of
+// key value pairs.
+enum Node {
+    Text(String),
+    Block(Vec<(String, Node)>),
+}
+
+// Splits into words, quoted strings and parentheses, each with its line.
+fn tokenize(text: &str) -> Result<Vec<(String, usize)>, DatError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => {}
+            '(' | ')' => tokens.push((c.to_string(), line)),
+            '"' => {
+                let mut word = String::new();
+                let start = line;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => {
+                            line += (c == '\n') as usize;
+                            word.push(c);
+                        }
+                        None => {
+                            return Err(DatError::Syntax {
+                                line: start,
+                                message: "unterminated string",
+                            })
+                        }
+                    }
+                }
+                // Quoted so it is never taken for a parenthesis.
+                tokens.push((format!("\"{}", word), start));
+            }
+            _ => {
+                let mut word = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push((word, line));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_block(
+    tokens: &[(String, usize)],
+    pos: &mut usize,
+    nested: bool,
+) -> Result<Vec<(String, Node)>, DatError> {
+    let mut pairs = Vec::new();
+    loop {
+        let Some((key, line)) = tokens.get(*pos) else {
+            if nested {
+                let line = tokens.last().map_or(1, |(_, line)| *line);
+                return Err(DatError::Syntax {
+                    line,
+                    message: "unclosed parenthesis",
+                });
+            }
+            return Ok(pairs);
+        };
+        *pos += 1;
+        if key == ")" {
+            if nested {
+                return Ok(pairs);
+            }
+            return Err(DatError::Syntax {
+                line: *line,
+                message: "unexpected closing parenthesis",
+            });
+        }
+        let value = match tokens.get(*pos).map(|(token, _)| token.as_str()) {
+            Some("(") => {
+                *pos += 1;
+                Node::Block(parse_block(tokens, pos, true)?)
+            }
+            Some(")") | None => {
+                return Err(DatError::Syntax {
+                    line: *line,
+                    message: "key without a value",
+                })
+            }
+            Some(token) => {
+                *pos += 1;
+                Node::Text(token.trim_start_matches('"').to_string())
+            }
+        };
+        pairs.push((key.trim_start_matches('"').to_string(), value));
+    }
+}
+
+fn parse_clrmamepro(text: &str) -> Result<Dat, DatError> {
+    let tokens = tokenize(text)?;
+    let mut dat = Dat::default();
+    for (key, node) in parse_block(&tokens, &mut 0, false)? {
+        let Node::Block(pairs) = node else { continue };
+        match key.as_str() {
+            "clrmamepro" => {
+                for (key, node) in pairs {
+                    if let ("name", Node::Text(name)) = (key.as_str(), node) {
+                        dat.name = name;
+                    }
+                }
+            }
+            "game" | "machine" | "resource" => {
+                let mut game = Game::default();
+                for (key, node) in pairs {
+                    match (key.as_str(), node) {
+                        ("name", Node::Text(name)) => game.name = name,
+                        ("description", Node::Text(description)) => game.description = description,
+                        ("rom", Node::Block(fields)) => {
+                            let mut rom = DatRom::default();
+                            for (key, node) in fields {
+                                if let Node::Text(value) = node {
+                                    rom.set(&key, &value);
+                                }
+                            }
+                            game.roms.push(rom);
+                        }
+                        _ => {}
+                    }
+                }
+                dat.games.push(game);
+            }
+            _ => {}
+        }
+    }
+    Ok(dat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE datafile>
+<datafile>
+  <header><name>Sega - Mega Drive - Genesis</name></header>
+  <game name="Test (World)">
+    <description>Test &amp; Co (World)</description>
+    <rom name="Test (World).md" size="3" crc="352441c2" status="verified"/>
+  </game>
+</datafile>
+"#;
+
+    const CLRMAMEPRO: &str = r#"clrmamepro (
+	name "Sega - Mega Drive - Genesis"
+)
+
+game (
+	name "Test (World)"
+	description "Test (World)"
+	rom ( name "Test (World).md" size 3 crc 352441C2 flags baddump )
+)
+"#;
+
+    #[test]
+    fn identifies_from_either_format() {
+        let hashes = Hashes::of(b"abc");
+        for text in [XML, CLRMAMEPRO] {
+            let dat = Dat::parse(text).unwrap();
+            assert_eq!(dat.name, "Sega - Mega Drive - Genesis");
+            let found = dat.identify(&hashes).unwrap();
+            assert_eq!(found.game.name, "Test (World)");
+            assert_eq!(found.rom.crc32, Some(0x352441C2));
+        }
+        let xml = Dat::parse(XML).unwrap();
+        assert_eq!(xml.games[0].description, "Test & Co (World)");
+        assert_eq!(
+            xml.identify(&hashes).unwrap().to_string(),
+            "Test (World) [verified]"
+        );
+        assert!(xml.identify(&Hashes::of(b"abd")).is_none());
+    }
+
+    #[test]
+    fn syntax_errors_report_their_line() {
+        let line = |text: &str| match Dat::parse(text) {
+            Err(DatError::Syntax { line, .. }) => line,
+            other => panic!("expected a syntax error, got {:?}", other),
+        };
+        assert_eq!(line("<datafile>\n\n<rom name=\"x\"/>\n</datafile>"), 3);
+        assert_eq!(line("<a>\n</b>"), 2);
+        assert_eq!(line("game (\n\tname \"a\nb\"\n\tdescription \"c\n"), 4);
+    }
+
+    #[test]
+    fn entities() {
+        assert_eq!(
+            unescape("a &lt;b&gt; &quot;c&quot; &apos;d&apos;"),
+            "a <b> \"c\" 'd'"
+        );
+        assert_eq!(unescape("&amp;lt;"), "&lt;");
+        assert_eq!(
+            unescape("Pok&#233;mon &#xE9;&#XE9; &#x1F600;"),
+            "Pokémon éé 😀"
+        );
+        // Unknown, malformed and unterminated references are left alone.
+        for text in [
+            "&nbsp;", "&#;", "&#x;", "&#xZZ;", "&#55296;", "& b;", "a & b", "&#65",
+        ] {
+            assert_eq!(unescape(text), text);
+        }
+        let xml = XML.replace("Test &amp; Co", "Caf&#233; &#x26; Co");
+        let dat = Dat::parse(&xml).unwrap();
+        assert_eq!(dat.games[0].description, "Café & Co (World)");
+    }
+}
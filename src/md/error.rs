@@ -0,0 +1,74 @@
+use std::fmt::{self, Display, Formatter};
+
+// Why a ROM could not be loaded, decoded or written.
+#[derive(Debug)]
+pub enum RomError {
+    Io(std::io::Error),
+    // A format name or file extension that is not recognised.
+    UnknownFormat(String),
+    // The image is `len` bytes but at least `needed` are required.
+    TooShort { len: usize, needed: usize },
+    // The SMD copier header is missing or malformed.
+    BadCopierHeader(&'static str),
+    // A format that stores words was given an odd number of bytes.
+    OddLength { len: usize },
+    // The even and odd chip images of a bank differ in size.
+    ChipSizeMismatch { even: usize, odd: usize },
+    // A patch file that is truncated or malformed.
+    BadPatch(&'static str),
+    // A change too large for the patch format to express.
+    PatchTooLarge { len: usize, max: usize },
+    // A CRC32 recorded in a patch does not match: `what` is "source" when the
+    // patch is for a different ROM, "target" when applying it went wrong and
+    // "patch" when the patch itself is damaged.
+    CrcMismatch { what: &'static str, expected: u32, actual: u32 },
+    // The ROM declares no SRAM and is not a known EEPROM game.
+    NoSaveMemory,
+    // The header SRAM block gives a range no cartridge SRAM could have.
+    BadSramRange { start: u32, end: u32 },
+}
+
+impl Display for RomError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{}", error),
+            Self::UnknownFormat(name) => write!(f, "unknown rom format {:?}", name),
+            Self::TooShort { len, needed } => {
+                write!(f, "rom is {} bytes, at least {} needed", len, needed)
+            }
+            Self::BadCopierHeader(reason) => write!(f, "bad copier header: {}", reason),
+            Self::OddLength { len } => write!(f, "odd length of {} bytes", len),
+            Self::ChipSizeMismatch { even, odd } => {
+                write!(f, "even chip is {} bytes but odd chip is {}", even, odd)
+            }
+            Self::BadPatch(reason) => write!(f, "bad patch: {}", reason),
+            Self::PatchTooLarge { len, max } => {
+                write!(f, "{} bytes is over the patch format limit of {}", len, max)
+            }
+            Self::CrcMismatch { what, expected, actual } => write!(
+                f,
+                "{} crc32 is {:08x}, patch expects {:08x}",
+                what, actual, expected
+            ),
+            Self::NoSaveMemory => write!(f, "rom has no save memory"),
+            Self::BadSramRange { start, end } => {
+                write!(f, "bad SRAM range {:#08x}-{:#08x}", start, end)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RomError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for RomError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error)
+    }
+}
@@ -0,0 +1,458 @@
+use std::fmt::Display;
+use std::ops::Range;
+
+use super::error::RomError;
+use super::region::Region;
+
+// Ref: https://www.zophar.net/fileuploads/2/10614uauyw/Genesis_ROM_Format.txt
+pub const HEADER_START: usize = 0x100;
+pub const HEADER_END: usize = 0x200;
+
+pub const CONSOLE: Range<usize> = 0x100..0x110;
+pub const COPYRIGHT: Range<usize> = 0x110..0x120;
+pub const TITLE: Range<usize> = 0x120..0x150;
+pub const OVERSEAS_TITLE: Range<usize> = 0x150..0x180;
+pub const PRODUCT_TYPE: Range<usize> = 0x180..0x182;
+pub const PRODUCT_CODE: Range<usize> = 0x182..0x18E;
+pub const CHECKSUM: Range<usize> = 0x18E..0x190;
+pub const DEVICES: Range<usize> = 0x190..0x1A0;
+pub const ROM_START: Range<usize> = 0x1A0..0x1A4;
+pub const ROM_END: Range<usize> = 0x1A4..0x1A8;
+pub const RAM_START: Range<usize> = 0x1A8..0x1AC;
+pub const RAM_END: Range<usize> = 0x1AC..0x1B0;
+pub const SRAM: Range<usize> = 0x1B0..0x1BC;
+pub const MODEM: Range<usize> = 0x1BC..0x1C8;
+pub const MEMO: Range<usize> = 0x1C8..0x1F0;
+pub const REGION: Range<usize> = 0x1F0..0x1F3;
+
+// A fixed width text field. Headers are meant to be space padded ASCII but
+// real ones contain Shift-JIS, NULs and garbage, so the raw bytes are kept
+// next to a trimmed, lossily decoded copy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Text {
+    pub raw: Vec<u8>,
+    pub text: String,
+}
+
+impl Text {
+    pub fn new(raw: &[u8]) -> Self {
+        let text = String::from_utf8_lossy(raw);
+        Self {
+            raw: raw.to_vec(),
+            text: text.trim_matches(|c: char| c == ' ' || c == '\0').to_string(),
+        }
+    }
+}
+
+impl Display for Text {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+// The cartridge header at 0x100-0x1FF.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub console: Text,
+    pub copyright: Text,
+    pub title: Text,
+    pub overseas_title: Text,
+    pub product_type: Text,
+    pub product_code: Text,
+    pub checksum: u16,
+    pub devices: Text,
+    pub rom_start: u32,
+    pub rom_end: u32,
+    pub ram_start: u32,
+    pub ram_end: u32,
+    pub sram: [u8; 12],
+    pub modem: Text,
+    pub memo: Text,
+    pub region: Text,
+    raw: [u8; HEADER_END - HEADER_START],
+}
+
+impl Header {
+    // Parses the header from a whole ROM image.
+    pub fn parse(data: &[u8]) -> Result<Self, RomError> {
+        let raw: [u8; HEADER_END - HEADER_START] = data
+            .get(HEADER_START..HEADER_END)
+            .ok_or(RomError::TooShort {
+                len: data.len(),
+                needed: HEADER_END,
+            })?
+            .try_into()
+            .unwrap();
+        let text = |range: Range<usize>| Text::new(&data[range]);
+        let long = |range: Range<usize>| u32::from_be_bytes(data[range].try_into().unwrap());
+        Ok(Self {
+            console: text(CONSOLE),
+            copyright: text(COPYRIGHT),
+            title: text(TITLE),
+            overseas_title: text(OVERSEAS_TITLE),
+            product_type: text(PRODUCT_TYPE),
+            product_code: text(PRODUCT_CODE),
+            checksum: u16::from_be_bytes(data[CHECKSUM].try_into().unwrap()),
+            devices: text(DEVICES),
+            rom_start: long(ROM_START),
+            rom_end: long(ROM_END),
+            ram_start: long(RAM_START),
+            ram_end: long(RAM_END),
+            sram: data[SRAM].try_into().unwrap(),
+            modem: text(MODEM),
+            memo: text(MEMO),
+            region: text(REGION),
+            raw,
+        })
+    }
+    // The 256 header bytes as found in the ROM.
+    pub fn raw(&self) -> &[u8] {
+        &self.raw
+    }
+    // Supported peripherals, in header order. Spaces and NULs are skipped.
+    pub fn devices(&self) -> Vec<Device> {
+        self.devices
+            .raw
+            .iter()
+            .filter(|&&code| code != b' ' && code != 0)
+            .map(|&code| Device::from_code(code))
+            .collect()
+    }
+    pub fn sram_info(&self) -> SramInfo {
+        SramInfo::parse(&self.sram)
+    }
+    pub fn modem_info(&self) -> Option<ModemInfo> {
+        ModemInfo::parse(&self.modem.raw)
+    }
+    pub fn regions(&self) -> Region {
+        Region::parse(&self.region.raw)
+    }
+}
+
+// Peripherals from the device support field at 0x190, one letter each.
+// Ref: https://plutiedev.com/rom-header#devices
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Device {
+    Joypad3,
+    Joypad6,
+    MasterSystemJoypad,
+    AnalogJoystick,
+    Multitap,
+    LightGun,
+    Activator,
+    Mouse,
+    Trackball,
+    Tablet,
+    Paddle,
+    Keyboard,
+    Serial,
+    Printer,
+    MegaCd,
+    Floppy,
+    Download,
+    Unknown(u8),
+}
+
+impl Device {
+    pub fn from_code(code: u8) -> Self {
+        match code {
+            b'J' => Device::Joypad3,
+            b'6' => Device::Joypad6,
+            b'0' => Device::MasterSystemJoypad,
+            b'A' => Device::AnalogJoystick,
+            b'4' => Device::Multitap,
+            b'G' => Device::LightGun,
+            b'L' => Device::Activator,
+            b'M' => Device::Mouse,
+            b'B' => Device::Trackball,
+            b'T' => Device::Tablet,
+            b'V' => Device::Paddle,
+            b'K' => Device::Keyboard,
+            b'R' => Device::Serial,
+            b'P' => Device::Printer,
+            b'C' => Device::MegaCd,
+            b'F' => Device::Floppy,
+            b'D' => Device::Download,
+            _ => Device::Unknown(code),
+        }
+    }
+    pub fn code(&self) -> u8 {
+        match self {
+            Device::Joypad3 => b'J',
+            Device::Joypad6 => b'6',
+            Device::MasterSystemJoypad => b'0',
+            Device::AnalogJoystick => b'A',
+            Device::Multitap => b'4',
+            Device::LightGun => b'G',
+            Device::Activator => b'L',
+            Device::Mouse => b'M',
+            Device::Trackball => b'B',
+            Device::Tablet => b'T',
+            Device::Paddle => b'V',
+            Device::Keyboard => b'K',
+            Device::Serial => b'R',
+            Device::Printer => b'P',
+            Device::MegaCd => b'C',
+            Device::Floppy => b'F',
+            Device::Download => b'D',
+            Device::Unknown(code) => *code,
+        }
+    }
+}
+
+impl Display for Device {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Device::Joypad3 => "3-button joypad",
+            Device::Joypad6 => "6-button joypad",
+            Device::MasterSystemJoypad => "Master System joypad",
+            Device::AnalogJoystick => "analog joystick",
+            Device::Multitap => "multitap",
+            Device::LightGun => "light gun",
+            Device::Activator => "Activator",
+            Device::Mouse => "mouse",
+            Device::Trackball => "trackball",
+            Device::Tablet => "tablet",
+            Device::Paddle => "paddle",
+            Device::Keyboard => "keyboard",
+            Device::Serial => "RS-232 serial",
+            Device::Printer => "printer",
+            Device::MegaCd => "Mega CD",
+            Device::Floppy => "floppy drive",
+            Device::Download => "download",
+            Device::Unknown(code) => return write!(f, "unknown ({:?})", *code as char),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Which data lines the save RAM is wired to. Odd and even mean one byte of
+// every word, at odd or even addresses.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SramLanes {
+    Both,
+    Even,
+    Odd,
+}
+
+// The external RAM block at 0x1B0: "RA", a type byte, 0x20, then the start and
+// end addresses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SramInfo {
+    // Whether the block carries the "RA" signature at all.
+    pub present: bool,
+    pub lanes: SramLanes,
+    // Battery backed, as opposed to cleared on power off.
+    pub backup: bool,
+    pub start: u32,
+    // Inclusive, as stored in the header.
+    pub end: u32,
+}
+
+impl SramInfo {
+    pub fn parse(raw: &[u8; 12]) -> Self {
+        let kind = raw[2];
+        Self {
+            present: raw[..2] == *b"RA",
+            lanes: match kind & 0x18 {
+                0x10 => SramLanes::Even,
+                0x18 => SramLanes::Odd,
+                _ => SramLanes::Both,
+            },
+            backup: kind & 0x40 != 0,
+            start: u32::from_be_bytes(raw[4..8].try_into().unwrap()),
+            end: u32::from_be_bytes(raw[8..12].try_into().unwrap()),
+        }
+    }
+    // The 12 header bytes for this block. An absent block is all spaces.
+    pub fn to_bytes(&self) -> [u8; 12] {
+        if !self.present {
+            return [b' '; 12];
+        }
+        let lanes = match self.lanes {
+            SramLanes::Both => 0x00,
+            SramLanes::Even => 0x10,
+            SramLanes::Odd => 0x18,
+        };
+        let backup = if self.backup { 0x40 } else { 0x00 };
+        let mut raw = [0; 12];
+        raw[..4].copy_from_slice(&[b'R', b'A', 0xA0 | backup | lanes, 0x20]);
+        raw[4..8].copy_from_slice(&self.start.to_be_bytes());
+        raw[8..12].copy_from_slice(&self.end.to_be_bytes());
+        raw
+    }
+    // Bus addresses the RAM is mapped to, end exclusive.
+    pub fn range(&self) -> Range<u32> {
+        self.start..self.end.wrapping_add(1)
+    }
+}
+
+impl Display for SramInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.present {
+            return write!(f, "none");
+        }
+        let lanes = match self.lanes {
+            SramLanes::Both => "both",
+            SramLanes::Even => "even",
+            SramLanes::Odd => "odd",
+        };
+        let kind = if self.backup { "backup" } else { "volatile" };
+        write!(f, "${:06X}-${:06X} {} {} bytes", self.start, self.end, kind, lanes)
+    }
+}
+
+// The modem field at 0x1BC: "MO", a four character company code, a two digit
+// game number, a dot and a version digit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModemInfo {
+    pub company: String,
+    pub number: String,
+    pub version: String,
+}
+
+impl ModemInfo {
+    // Returns None for the usual blank field and anything without the "MO"
+    // signature.
+    pub fn parse(raw: &[u8]) -> Option<Self> {
+        let raw = raw.strip_prefix(b"MO")?;
+        // Short fields keep whatever part of them is there.
+        let field = |range: Range<usize>| {
+            let end = range.end.min(raw.len());
+            Text::new(raw.get(range.start..end).unwrap_or_default()).text
+        };
+        Some(Self {
+            company: field(0..4),
+            number: field(4..6),
+            version: field(7..8),
+        })
+    }
+}
+
+impl Display for ModemInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} #{} v{}", self.company, self.number, self.version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image() -> Vec<u8> {
+        let mut data = vec![b' '; HEADER_END];
+        data[CONSOLE].copy_from_slice(b"SEGA MEGA DRIVE ");
+        data
+    }
+
+    #[test]
+    fn short_images_are_rejected() {
+        assert!(matches!(
+            Header::parse(&[0; HEADER_END - 1]),
+            Err(RomError::TooShort {
+                len: 0x1FF,
+                needed: HEADER_END
+            })
+        ));
+        assert!(Header::parse(&image()).is_ok());
+    }
+
+    #[test]
+    fn text_fields_are_trimmed_and_decoded_lossily() {
+        let mut data = image();
+        data[TITLE][..7].copy_from_slice(b"\0SONIC\0");
+        data[OVERSEAS_TITLE][..4].copy_from_slice(&[b'A', 0xFF, b'B', b' ']);
+        data[ROM_END].copy_from_slice(&0x000F_FFFFu32.to_be_bytes());
+        let header = Header::parse(&data).unwrap();
+        assert_eq!(header.console.text, "SEGA MEGA DRIVE");
+        assert_eq!(header.title.text, "SONIC");
+        assert_eq!(header.title.raw.len(), TITLE.len());
+        assert_eq!(header.overseas_title.text, "A\u{FFFD}B");
+        assert_eq!(header.rom_end, 0x000F_FFFF);
+        assert_eq!(header.raw(), &data[HEADER_START..]);
+    }
+
+    #[test]
+    fn devices() {
+        let codes = b"J60A4GLMBTVKRPCFD";
+        for &code in codes {
+            let device = Device::from_code(code);
+            assert_ne!(device, Device::Unknown(code), "{:?}", code as char);
+            assert_eq!(device.code(), code);
+        }
+        assert_eq!(Device::from_code(b'Z'), Device::Unknown(b'Z'));
+
+        let mut data = image();
+        data[DEVICES][..4].copy_from_slice(b"J 6\0");
+        data[DEVICES][4] = b'Z';
+        let header = Header::parse(&data).unwrap();
+        assert_eq!(
+            header.devices(),
+            [Device::Joypad3, Device::Joypad6, Device::Unknown(b'Z')]
+        );
+    }
+
+    #[test]
+    fn sram_block() {
+        let mut data = image();
+        let header = Header::parse(&data).unwrap();
+        assert!(!header.sram_info().present);
+        assert_eq!(header.sram_info().to_string(), "none");
+
+        data[SRAM].copy_from_slice(&[
+            b'R', b'A', 0xF8, 0x20, 0x00, 0x20, 0x00, 0x01, 0x00, 0x20, 0x3F, 0xFF,
+        ]);
+        let sram = Header::parse(&data).unwrap().sram_info();
+        assert_eq!(
+            sram,
+            SramInfo {
+                present: true,
+                lanes: SramLanes::Odd,
+                backup: true,
+                start: 0x20_0001,
+                end: 0x20_3FFF,
+            }
+        );
+        assert_eq!(sram.range(), 0x20_0001..0x20_4000);
+        assert_eq!(sram.to_bytes(), data[SRAM]);
+
+        for (kind, lanes, backup) in [
+            (0xA0, SramLanes::Both, false),
+            (0xB0, SramLanes::Even, false),
+            (0xE0, SramLanes::Both, true),
+        ] {
+            data[SRAM.start + 2] = kind;
+            let sram = SramInfo::parse(&data[SRAM].try_into().unwrap());
+            assert_eq!((sram.lanes, sram.backup), (lanes, backup));
+            assert_eq!(sram.to_bytes(), data[SRAM]);
+        }
+        assert_eq!(SramInfo::parse(&[b' '; 12]).to_bytes(), [b' '; 12]);
+    }
+
+    #[test]
+    fn modem_field() {
+        let mut data = image();
+        assert_eq!(Header::parse(&data).unwrap().modem_info(), None);
+
+        data[MODEM].copy_from_slice(b"MOSEGA05.1  ");
+        let modem = Header::parse(&data).unwrap().modem_info().unwrap();
+        assert_eq!(
+            modem,
+            ModemInfo {
+                company: "SEGA".to_string(),
+                number: "05".to_string(),
+                version: "1".to_string(),
+            }
+        );
+        assert_eq!(modem.to_string(), "SEGA #05 v1");
+
+        // Garbage after the signature is kept, short fields are empty.
+        data[MODEM].copy_from_slice(b"MO\xFF\0\0\0\0\0\0\0\0\0");
+        let modem = Header::parse(&data).unwrap().modem_info().unwrap();
+        assert_eq!(modem.company, "\u{FFFD}");
+        assert_eq!(modem.number, "");
+        assert_eq!(ModemInfo::parse(b"MOAB").unwrap().company, "AB");
+        assert_eq!(ModemInfo::parse(b"MO").unwrap().version, "");
+        assert_eq!(ModemInfo::parse(b"mOSEGA05.1"), None);
+    }
+}
@@ -0,0 +1,244 @@
+use super::error::RomError;
+use super::rom::Rom;
+
+// International Patching System: "PATCH", records, "EOF", then optionally a
+// three byte length to truncate the file to.
+// Ref: https://zerosoft.zophar.net/ips.php
+const MAGIC: &[u8] = b"PATCH";
+const FOOTER: &[u8] = b"EOF";
+// Offsets are three bytes, and one that spells "EOF" would end the patch.
+const MAX_SIZE: usize = 0x100_0000;
+const EOF_OFFSET: usize = 0x454F46;
+const MAX_RECORD: usize = 0xFFFF;
+
+// Applies an IPS patch to a ROM, then re-reads the header and, if asked,
+// fixes the checksum. On error the ROM is left unchanged.
+pub fn apply(rom: &mut Rom, patch: &[u8], fix_checksum: bool) -> Result<(), RomError> {
+    let mut data = rom.data().to_vec();
+    apply_to(&mut data, patch)?;
+    rom.replace_data(data)?;
+    if fix_checksum {
+        rom.fix_checksum();
+    }
+    Ok(())
+}
+
+// Applies an IPS patch to raw bytes. Records past the end grow the data,
+// filling any gap with zeros.
+pub fn apply_to(data: &mut Vec<u8>, patch: &[u8]) -> Result<(), RomError> {
+    let mut rest = patch
+        .strip_prefix(MAGIC)
+        .ok_or(RomError::BadPatch("missing PATCH signature"))?;
+    let mut take = |len: usize| -> Result<&[u8], RomError> {
+        if rest.len() < len {
+            return Err(RomError::BadPatch("truncated record"));
+        }
+        let (taken, remaining) = rest.split_at(len);
+        rest = remaining;
+        Ok(taken)
+    };
+    loop {
+        let offset = take(3)?;
+        if offset == FOOTER {
+            break;
+        }
+        let offset = u32::from_be_bytes([0, offset[0], offset[1], offset[2]]) as usize;
+        let size = u16::from_be_bytes(take(2)?.try_into().unwrap()) as usize;
+        // A zero size marks a run length record: count, then the byte.
+        let (count, bytes) = if size == 0 {
+            let count = u16::from_be_bytes(take(2)?.try_into().unwrap()) as usize;
+            (count, None)
+        } else {
+            (size, Some(take(size)?))
+        };
+        if data.len() < offset + count {
+            data.resize(offset + count, 0);
+        }
+        match bytes {
+            Some(bytes) => data[offset..offset + count].copy_from_slice(bytes),
+            None => {
+                let value = take(1)?[0];
+                data[offset..offset + count].fill(value);
+            }
+        }
+    }
+    match rest {
+        [] => {}
+        [a, b, c] => data.truncate(u32::from_be_bytes([0, *a, *b, *c]) as usize),
+        _ => return Err(RomError::BadPatch("trailing bytes after EOF")),
+    }
+    Ok(())
+}
+
+// Builds an IPS patch turning `source` into `target`.
+pub fn create(source: &Rom, target: &Rom) -> Result<Vec<u8>, RomError> {
+    create_from(source.data(), target.data())
+}
+
+// Builds a small IPS patch from raw bytes: nearby changes share a record,
+// long runs of one byte use run length records, and a shorter target is
+// truncated with the extension.
+pub fn create_from(source: &[u8], target: &[u8]) -> Result<Vec<u8>, RomError> {
+    if target.len() > MAX_SIZE {
+        return Err(RomError::PatchTooLarge {
+            len: target.len(),
+            max: MAX_SIZE,
+        });
+    }
+    let same = |i: usize| i < source.len() && source[i] == target[i];
+    let mut patch = MAGIC.to_vec();
+    let mut i = 0;
+    while i < target.len() {
+        if same(i) {
+            i += 1;
+            continue;
+        }
+        // Extend the change over short equal gaps, which cost less to repeat
+        // than a new record header.
+        let start = i;
+        let mut end = i;
+        while end < target.len() && end - start < MAX_RECORD {
+            if !same(end) {
+                end += 1;
+                continue;
+            }
+            let gap = (end..target.len()).take_while(|&j| same(j)).count();
+            if gap > 5 || end + gap == target.len() {
+                break;
+            }
+            end = (end + gap).min(start + MAX_RECORD);
+        }
+        write_records(&mut patch, target, start, end);
+        i = end;
+    }
+    patch.extend(FOOTER);
+    if target.len() < source.len() {
+        patch.extend(&(target.len() as u32).to_be_bytes()[1..]);
+    }
+    Ok(patch)
+}
+
+// Writes target[start..end] as literal and run length records.
+fn write_records(patch: &mut Vec<u8>, target: &[u8], start: usize, end: usize) {
+    let mut literal = start;
+    let mut i = start;
+    while i < end {
+        let run = target[i..end].iter().take_while(|&&b| b == target[i]).count();
+        // A run length record is 8 bytes; only worth it for longer runs. One
+        // cannot start at the "EOF" offset since it has no byte to step back
+        // over.
+        if run > 8 && i != EOF_OFFSET {
+            if literal < i {
+                write_literal(patch, target, literal, i);
+            }
+            write_offset(patch, i);
+            patch.extend([0, 0]);
+            patch.extend((run as u16).to_be_bytes());
+            patch.push(target[i]);
+            literal = i + run;
+        }
+        i += run;
+    }
+    if literal < end {
+        write_literal(patch, target, literal, end);
+    }
+}
+
+fn write_literal(patch: &mut Vec<u8>, target: &[u8], start: usize, end: usize) {
+    let mut pos = start;
+    while pos < end {
+        // Step back a byte rather than write an offset that reads as "EOF".
+        let offset = if pos == EOF_OFFSET { pos - 1 } else { pos };
+        let chunk_end = (offset + MAX_RECORD).min(end);
+        write_offset(patch, offset);
+        patch.extend(((chunk_end - offset) as u16).to_be_bytes());
+        patch.extend(&target[offset..chunk_end]);
+        pos = chunk_end;
+    }
+}
+
+fn write_offset(patch: &mut Vec<u8>, offset: usize) {
+    patch.extend(&(offset as u32).to_be_bytes()[1..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md::rom_fmt::Format;
+
+    fn round_trip(source: &[u8], target: &[u8]) {
+        let patch = create_from(source, target).unwrap();
+        let mut data = source.to_vec();
+        apply_to(&mut data, &patch).unwrap();
+        assert!(data == target, "patched data differs");
+    }
+
+    #[test]
+    fn create_and_apply() {
+        let source: Vec<u8> = (0..0x1000).map(|i| (i * 13) as u8).collect();
+        let mut target = source.clone();
+        target[0x10] ^= 0xFF;
+        target[0x14] ^= 0xFF;
+        target[0x200..0x300].fill(0xAA);
+        round_trip(&source, &target);
+        // Growing, with a gap of zeros, and truncating.
+        target.extend([0; 0x40]);
+        target.push(1);
+        round_trip(&source, &target);
+        round_trip(&source, &source[..0x800]);
+    }
+
+    #[test]
+    fn change_at_the_eof_offset() {
+        let source = vec![0; EOF_OFFSET + 0x20];
+        let mut target = source.clone();
+        target[EOF_OFFSET..EOF_OFFSET + 0x10].fill(0x55);
+        // A record there would end the patch early.
+        round_trip(&source, &target);
+    }
+
+    #[test]
+    fn malformed_patches() {
+        let mut data = vec![1, 2, 3, 4];
+        let bad = |data: &mut Vec<u8>, patch: &[u8]| match apply_to(data, patch) {
+            Err(RomError::BadPatch(reason)) => reason,
+            other => panic!("expected a bad patch, got {:?}", other),
+        };
+        assert_eq!(bad(&mut data, b"PATCHE"), "truncated record");
+        assert_eq!(
+            bad(&mut data, b"IPS\0\0\0\0\0\x01\x09EOF"),
+            "missing PATCH signature"
+        );
+        assert_eq!(
+            bad(&mut data, b"PATCH\0\0\0\0\x05\x09EOF"),
+            "truncated record"
+        );
+        let trailing = b"PATCH\0\0\0\0\x01\x09EOF\0\0\x02\0";
+        assert_eq!(bad(&mut data, trailing), "trailing bytes after EOF");
+        assert_eq!(
+            bad(&mut data, &trailing[..trailing.len() - 2]),
+            "trailing bytes after EOF"
+        );
+
+        let mut data = vec![1, 2, 3, 4];
+        apply_to(&mut data, &trailing[..trailing.len() - 1]).unwrap();
+        assert_eq!(data, [9, 2]);
+    }
+
+    #[test]
+    fn failed_apply_leaves_the_rom_alone() {
+        let mut data = vec![0; 0x400];
+        data[0x100..0x104].copy_from_slice(b"SEGA");
+        let mut rom = Rom::from_bytes(data.clone(), Format::Bin).unwrap();
+        // Truncating below the header fails once the header is re-read.
+        let patch = b"PATCH\0\0\0\0\x01\x09EOF\0\0\x10";
+        assert!(matches!(
+            apply(&mut rom, patch, false),
+            Err(RomError::TooShort { .. })
+        ));
+        assert_eq!(rom.data(), data);
+
+        let too_large = create_from(&[], &vec![0; MAX_SIZE + 1]);
+        assert!(matches!(too_large, Err(RomError::PatchTooLarge { .. })));
+    }
+}
@@ -0,0 +1,265 @@
+use super::console::Console;
+use super::header::Header;
+use super::rom::Rom;
+
+// The cartridge area of the 68k address space.
+pub const CARTRIDGE_SIZE: usize = 0x40_0000;
+// Bank size and register base of the Sega mapper used by Super Street
+// Fighter II and large homebrew.
+// Ref: https://plutiedev.com/beyond-4mb
+pub const SEGA_BANK_SIZE: usize = 0x8_0000;
+pub const SEGA_REGISTERS: u32 = 0xA1_30F1;
+// Sonic & Knuckles maps itself at 0 and the cartridge on top of it at 2 MB.
+pub const LOCK_ON_BASE: usize = 0x20_0000;
+// Where the 32X shows the cartridge once its adapter is enabled: the first
+// 512 KB fixed, then a 1 MB window picked by the bank set register.
+// Ref: https://segaretro.org/Sega_32X/Memory_map
+pub const MARS_FIXED: u32 = 0x88_0000;
+pub const MARS_BANKED: u32 = 0x90_0000;
+pub const MARS_END: u32 = 0xA0_0000;
+pub const MARS_BANK_SIZE: usize = 0x10_0000;
+pub const MARS_BANK_REGISTER: u32 = 0xA1_5105;
+
+// How 68k addresses in the cartridge area reach bytes of the ROM file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mapper {
+    // Addresses are file offsets.
+    Flat { size: usize },
+    // Eight 512 KB windows, each showing the bank in `banks`. Window 0 is
+    // fixed to bank 0.
+    Sega { banks: [u8; 8], size: usize },
+    // A lock-on image: the base cartridge, then the locked on one, which is
+    // mirrored to fill the upper 2 MB.
+    LockOn { base: usize, locked: usize },
+    // A 32X cartridge, seen both at 0 as before the adapter is enabled and in
+    // the adapter's windows, with `bank` in the banked one.
+    Mars { bank: u8, size: usize },
+}
+
+impl Mapper {
+    // Picks the mapper from the header and size: images over 4 MB or with a
+    // "SEGA SSF" console string use the Sega mapper in its power on state,
+    // Sonic & Knuckles with a second header at 2 MB is a lock-on image.
+    pub fn detect(rom: &Rom) -> Self {
+        let header = rom.header();
+        let size = rom.data().len();
+        if rom.console() == Console::Sega32X {
+            return Self::Mars { bank: 0, size };
+        }
+        if size > CARTRIDGE_SIZE || header.console.text.starts_with("SEGA SSF") {
+            return Self::Sega {
+                banks: [0, 1, 2, 3, 4, 5, 6, 7],
+                size,
+            };
+        }
+        let locked = rom.data().get(LOCK_ON_BASE..).and_then(|data| Header::parse(data).ok());
+        if header.product_code.text.starts_with("MK-1563")
+            && locked.is_some_and(|locked| locked.console.text.contains("SEGA"))
+        {
+            return Self::LockOn {
+                base: LOCK_ON_BASE,
+                locked: size - LOCK_ON_BASE,
+            };
+        }
+        Self::Flat { size }
+    }
+
+    // The file offset a cartridge address reads, if any.
+    pub fn translate(&self, address: u32) -> Option<usize> {
+        let offset = match self {
+            Self::Mars { bank, .. } if address >= MARS_FIXED => match address {
+                MARS_FIXED..MARS_BANKED => (address - MARS_FIXED) as usize,
+                MARS_BANKED..MARS_END => {
+                    *bank as usize * MARS_BANK_SIZE + (address - MARS_BANKED) as usize
+                }
+                _ => return None,
+            },
+            _ if address as usize >= CARTRIDGE_SIZE => return None,
+            Self::Flat { .. } | Self::Mars { .. } => address as usize,
+            Self::Sega { banks, .. } => {
+                let address = address as usize;
+                let bank = banks[address / SEGA_BANK_SIZE] as usize;
+                bank * SEGA_BANK_SIZE + address % SEGA_BANK_SIZE
+            }
+            Self::LockOn { base, locked } => match (address as usize).checked_sub(*base) {
+                None => address as usize,
+                Some(_) if *locked == 0 => return None,
+                Some(offset) => base + offset % locked,
+            },
+        };
+        (offset < self.size()).then_some(offset)
+    }
+
+    // The first address that reads a file offset, if the current mapping
+    // shows it at all.
+    pub fn address_of(&self, offset: usize) -> Option<u32> {
+        if offset >= self.size() {
+            return None;
+        }
+        match self {
+            Self::Flat { .. } => (offset < CARTRIDGE_SIZE).then_some(offset as u32),
+            Self::Sega { banks, .. } => {
+                let bank = offset / SEGA_BANK_SIZE;
+                let window = banks.iter().position(|&b| b as usize == bank)?;
+                Some((window * SEGA_BANK_SIZE + offset % SEGA_BANK_SIZE) as u32)
+            }
+            Self::LockOn { .. } => Some(offset as u32),
+            Self::Mars { .. } => (offset < CARTRIDGE_SIZE).then_some(offset as u32),
+        }
+    }
+
+    // Size of the ROM file the mapper reads from.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Flat { size } | Self::Sega { size, .. } | Self::Mars { size, .. } => *size,
+            Self::LockOn { base, locked } => base + locked,
+        }
+    }
+
+    // Handles a byte write to a bank register: 0xA130F3 to 0xA130FF for
+    // windows 1 to 7 of the Sega mapper, or the 32X bank set register.
+    // Returns whether the write was a bank switch.
+    pub fn write(&mut self, address: u32, value: u8) -> bool {
+        match self {
+            Self::Sega { banks, .. } => match address.checked_sub(SEGA_REGISTERS) {
+                Some(delta) if delta > 0 && delta <= 14 && delta.is_multiple_of(2) => {
+                    banks[delta as usize / 2] = value;
+                    true
+                }
+                _ => false,
+            },
+            Self::Mars { bank, .. } if address == MARS_BANK_REGISTER => {
+                *bank = value & 3;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // The locked on cartridge of a lock-on image, header and all.
+    pub fn locked_on<'a>(&self, data: &'a [u8]) -> Option<&'a [u8]> {
+        match self {
+            Self::LockOn { base, .. } => data.get(*base..),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md::rom_fmt::Format;
+
+    fn image(size: usize, console: &[u8; 16]) -> Vec<u8> {
+        let mut data = vec![0; size];
+        data[0x100..0x110].copy_from_slice(console);
+        data
+    }
+
+    fn rom(data: Vec<u8>) -> Rom {
+        Rom::from_bytes(data, Format::Bin).unwrap()
+    }
+
+    #[test]
+    fn flat() {
+        let mapper = Mapper::detect(&rom(image(0x1000, b"SEGA MEGA DRIVE ")));
+        assert_eq!(mapper, Mapper::Flat { size: 0x1000 });
+        assert_eq!(mapper.translate(0x10), Some(0x10));
+        assert_eq!(mapper.translate(0x1000), None);
+        assert_eq!(mapper.address_of(0x20), Some(0x20));
+        assert_eq!(mapper.address_of(0x1000), None);
+    }
+
+    #[test]
+    fn sega_bank_switching() {
+        let mut mapper = Mapper::detect(&rom(image(10 * SEGA_BANK_SIZE, b"SEGA MEGA DRIVE ")));
+        assert_eq!(
+            mapper,
+            Mapper::Sega {
+                banks: [0, 1, 2, 3, 4, 5, 6, 7],
+                size: 10 * SEGA_BANK_SIZE
+            }
+        );
+        assert_eq!(mapper.translate(0x08_0010), Some(0x08_0010));
+        assert_eq!(mapper.translate(0x40_0000), None);
+
+        // 0xA130F3 selects the bank in window 1, 0xA130FF window 7.
+        assert!(mapper.write(0xA1_30F3, 9));
+        assert!(mapper.write(0xA1_30FF, 2));
+        assert_eq!(mapper.translate(0x08_0010), Some(9 * SEGA_BANK_SIZE + 0x10));
+        assert_eq!(mapper.translate(0x38_0010), Some(2 * SEGA_BANK_SIZE + 0x10));
+        assert_eq!(
+            mapper.address_of(9 * SEGA_BANK_SIZE + 0x10),
+            Some(0x08_0010)
+        );
+        // Bank 1 is no longer shown; bank 2 is in both windows 2 and 7.
+        assert_eq!(mapper.address_of(SEGA_BANK_SIZE), None);
+        assert_eq!(mapper.address_of(2 * SEGA_BANK_SIZE), Some(0x10_0000));
+
+        // Window 0 is fixed, and the registers are at odd addresses.
+        assert!(!mapper.write(0xA1_30F1, 5));
+        assert!(!mapper.write(0xA1_30F4, 5));
+        assert!(!mapper.write(0xA1_3101, 5));
+        assert_eq!(mapper.translate(0x10), Some(0x10));
+
+        let small = Mapper::detect(&rom(image(SEGA_BANK_SIZE, b"SEGA SSF        ")));
+        assert!(matches!(small, Mapper::Sega { .. }));
+    }
+
+    #[test]
+    fn lock_on_mirrors_the_locked_cartridge() {
+        let mut data = image(LOCK_ON_BASE, b"SEGA MEGA DRIVE ");
+        data[0x182..0x189].copy_from_slice(b"MK-1563");
+        data.extend(image(0x4_0000, b"SEGA GENESIS    "));
+        let mapper = Mapper::detect(&rom(data.clone()));
+        assert_eq!(
+            mapper,
+            Mapper::LockOn {
+                base: LOCK_ON_BASE,
+                locked: 0x4_0000
+            }
+        );
+        assert_eq!(mapper.translate(0x10), Some(0x10));
+        assert_eq!(mapper.translate(0x20_0010), Some(0x20_0010));
+        assert_eq!(mapper.translate(0x24_0010), Some(0x20_0010));
+        assert_eq!(mapper.translate(0x3F_FFFF), Some(0x23_FFFF));
+        assert_eq!(mapper.address_of(0x20_0010), Some(0x20_0010));
+        assert_eq!(mapper.locked_on(&data).unwrap().len(), 0x4_0000);
+
+        // Without a second header it is a plain 2 MB game.
+        data.truncate(LOCK_ON_BASE + 0x100);
+        data.resize(LOCK_ON_BASE + 0x4_0000, 0);
+        assert!(matches!(Mapper::detect(&rom(data)), Mapper::Flat { .. }));
+    }
+
+    #[test]
+    fn mars_windows() {
+        let mut mapper = Mapper::detect(&rom(image(0x30_0000, b"SEGA 32X        ")));
+        assert_eq!(
+            mapper,
+            Mapper::Mars {
+                bank: 0,
+                size: 0x30_0000
+            }
+        );
+        assert_eq!(mapper.translate(0x100), Some(0x100));
+        assert_eq!(mapper.translate(0x88_0100), Some(0x100));
+        assert_eq!(mapper.translate(0x90_0010), Some(0x10));
+        assert_eq!(mapper.translate(0xA0_0000), None);
+
+        assert!(mapper.write(MARS_BANK_REGISTER, 6));
+        assert_eq!(
+            mapper,
+            Mapper::Mars {
+                bank: 2,
+                size: 0x30_0000
+            }
+        );
+        assert_eq!(mapper.translate(0x90_0010), Some(0x20_0010));
+        assert_eq!(mapper.translate(0x9F_FFFF), Some(0x2F_FFFF));
+        assert!(mapper.write(MARS_BANK_REGISTER, 3));
+        assert_eq!(mapper.translate(0x90_0010), None);
+        assert!(!mapper.write(0xA1_30F3, 1));
+        assert_eq!(mapper.address_of(0x100), Some(0x100));
+    }
+}
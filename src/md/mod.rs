@@ -1,2 +1,17 @@
+pub mod analysis;
+pub mod bps;
+pub mod checksum;
+pub mod console;
+pub mod dat;
+pub mod error;
+pub mod hash;
+pub mod header;
+pub mod ips;
+pub mod mapper;
+mod patch_util;
+pub mod region;
 pub mod rom;
-pub mod rom_fmt;
\ No newline at end of file
+pub mod rom_fmt;
+pub mod save;
+pub mod ups;
+pub mod vectors;
\ No newline at end of file
@@ -0,0 +1,137 @@
+use super::error::RomError;
+use super::mapper::SEGA_BANK_SIZE;
+
+// The pieces BPS and UPS have in common: a signature, numbers of seven bits
+// per byte and a footer of three CRC32s.
+const FOOTER_SIZE: usize = 12;
+
+// The largest output accepted: every bank the Sega mapper can select. Sizes
+// are read from the patch, so a damaged one could otherwise ask for any
+// amount of memory.
+const MAX_TARGET_SIZE: usize = 0x100 * SEGA_BANK_SIZE;
+
+// Splits a BPS or UPS patch after checking its signature, returning the body
+// (signature included) and the three CRC32s of the footer.
+pub(super) fn split_footer<'a>(
+    patch: &'a [u8],
+    magic: &[u8],
+) -> Result<(&'a [u8], [u32; 3]), RomError> {
+    if !patch.starts_with(magic) {
+        return Err(RomError::BadPatch("missing signature"));
+    }
+    if patch.len() < magic.len() + FOOTER_SIZE {
+        return Err(RomError::BadPatch("truncated patch"));
+    }
+    let (body, footer) = patch.split_at(patch.len() - FOOTER_SIZE);
+    let crc = |i: usize| u32::from_le_bytes(footer[i * 4..i * 4 + 4].try_into().unwrap());
+    Ok((body, [crc(0), crc(1), crc(2)]))
+}
+
+pub(super) fn check_crc(what: &'static str, expected: u32, actual: u32) -> Result<(), RomError> {
+    if expected != actual {
+        return Err(RomError::CrcMismatch {
+            what,
+            expected,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+// Reads the variable length numbers BPS and UPS share: seven bits per byte,
+// least significant first, with the top bit marking the last byte.
+pub(super) struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(super) fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+    pub(super) fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+    pub(super) fn take(&mut self, len: usize) -> Result<&'a [u8], RomError> {
+        if self.data.len() < len {
+            return Err(RomError::BadPatch("truncated patch"));
+        }
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(taken)
+    }
+    pub(super) fn byte(&mut self) -> Result<u8, RomError> {
+        Ok(self.take(1)?[0])
+    }
+    // A size field, refused if it is over MAX_TARGET_SIZE.
+    pub(super) fn size(&mut self) -> Result<usize, RomError> {
+        usize::try_from(self.number()?)
+            .ok()
+            .filter(|&size| size <= MAX_TARGET_SIZE)
+            .ok_or(RomError::BadPatch("size too large"))
+    }
+    pub(super) fn number(&mut self) -> Result<u64, RomError> {
+        let overflow = || RomError::BadPatch("number too large");
+        let mut number = 0u64;
+        let mut shift = 1u64;
+        loop {
+            let byte = self.byte()?;
+            number = (byte as u64 & 0x7F)
+                .checked_mul(shift)
+                .and_then(|value| number.checked_add(value))
+                .ok_or_else(overflow)?;
+            if byte & 0x80 != 0 {
+                return Ok(number);
+            }
+            shift = shift.checked_mul(128).ok_or_else(overflow)?;
+            number = number.checked_add(shift).ok_or_else(overflow)?;
+        }
+    }
+}
+
+pub(super) fn write_number(out: &mut Vec<u8>, mut number: u64) {
+    loop {
+        let low = (number & 0x7F) as u8;
+        number >>= 7;
+        if number == 0 {
+            out.push(0x80 | low);
+            return;
+        }
+        out.push(low);
+        number -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_round_trip() {
+        let mut out = Vec::new();
+        let numbers = [0, 1, 0x7F, 0x80, 0x407F, 0x4080, u32::MAX as u64, u64::MAX];
+        for number in numbers {
+            write_number(&mut out, number);
+        }
+        // Each extra byte carries an implied one, so 0x80 still fits in two.
+        assert_eq!(&out[..6], [0x80, 0x81, 0xFF, 0x00, 0x80, 0x7F]);
+        let mut reader = Reader::new(&out);
+        for number in numbers {
+            assert_eq!(reader.number().unwrap(), number);
+        }
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn overlong_numbers_are_rejected() {
+        let mut reader = Reader::new(&[0x7F; 11]);
+        assert!(matches!(
+            reader.number(),
+            Err(RomError::BadPatch("number too large"))
+        ));
+        let mut reader = Reader::new(&[0x00, 0x00]);
+        assert!(matches!(
+            reader.number(),
+            Err(RomError::BadPatch("truncated patch"))
+        ));
+    }
+}
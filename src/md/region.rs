@@ -0,0 +1,162 @@
+use std::fmt::Display;
+use std::ops::{BitAnd, BitOr, BitOrAssign};
+
+// Territories from the region field at 0x1F0. Old headers list letters
+// ("JUE"), newer ones a single hex digit whose bits are these flags.
+// Ref: https://plutiedev.com/rom-header#region
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Region(u8);
+
+impl Region {
+    // Japan, 60 Hz.
+    pub const JAPAN: Region = Region(0x1);
+    // Asian PAL territories, 50 Hz.
+    pub const ASIA: Region = Region(0x2);
+    // Americas, 60 Hz.
+    pub const AMERICAS: Region = Region(0x4);
+    // Europe, 50 Hz.
+    pub const EUROPE: Region = Region(0x8);
+    pub const NONE: Region = Region(0);
+    pub const ALL: Region = Region(0xF);
+
+    const PAL: Region = Region(Self::ASIA.0 | Self::EUROPE.0);
+    const NTSC: Region = Region(Self::JAPAN.0 | Self::AMERICAS.0);
+
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+    // Unknown bits are dropped.
+    pub fn from_bits(bits: u8) -> Self {
+        Self(bits & Self::ALL.0)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+    pub fn contains(&self, other: Region) -> bool {
+        self.0 & other.0 == other.0
+    }
+    pub fn intersects(&self, other: Region) -> bool {
+        self.0 & other.0 != 0
+    }
+    pub fn is_pal_only(&self) -> bool {
+        !self.is_empty() && !self.intersects(Self::NTSC)
+    }
+    pub fn is_ntsc_only(&self) -> bool {
+        !self.is_empty() && !self.intersects(Self::PAL)
+    }
+
+    // Parses the region field in either convention. Case, surrounding spaces
+    // and NULs are ignored. A lone "E" is Europe, not the 0xE mask, since
+    // that is what such headers mean in practice. Characters that fit
+    // neither convention are skipped.
+    pub fn parse(raw: &[u8]) -> Self {
+        let text: Vec<u8> = raw
+            .iter()
+            .filter(|&&c| c != b' ' && c != 0)
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        let is_letter = |c: &u8| matches!(c, b'J' | b'U' | b'E');
+        if let [digit] = text[..] {
+            if !is_letter(&digit) {
+                if let Some(bits) = (digit as char).to_digit(16) {
+                    return Self::from_bits(bits as u8);
+                }
+            }
+        }
+        let mut region = Self::NONE;
+        for c in &text {
+            region |= match c {
+                b'J' => Self::JAPAN,
+                b'U' => Self::AMERICAS,
+                b'E' => Self::EUROPE,
+                _ => Self::NONE,
+            };
+        }
+        region
+    }
+
+    // Old style letters, e.g. "JUE". Asia has no letter and is left out.
+    pub fn to_letters(&self) -> String {
+        let mut letters = String::new();
+        for (flag, letter) in [(Self::JAPAN, 'J'), (Self::AMERICAS, 'U'), (Self::EUROPE, 'E')] {
+            if self.contains(flag) {
+                letters.push(letter);
+            }
+        }
+        letters
+    }
+    // New style single digit, e.g. 'F'.
+    pub fn to_hex(&self) -> char {
+        char::from_digit(self.0 as u32, 16).unwrap().to_ascii_uppercase()
+    }
+}
+
+impl BitOr for Region {
+    type Output = Region;
+    fn bitor(self, rhs: Region) -> Region {
+        Region(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for Region {
+    fn bitor_assign(&mut self, rhs: Region) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl BitAnd for Region {
+    type Output = Region;
+    fn bitand(self, rhs: Region) -> Region {
+        Region(self.0 & rhs.0)
+    }
+}
+
+impl Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<&str> = [
+            (Self::JAPAN, "Japan"),
+            (Self::ASIA, "Asia"),
+            (Self::AMERICAS, "Americas"),
+            (Self::EUROPE, "Europe"),
+        ]
+        .iter()
+        .filter(|(flag, _)| self.contains(*flag))
+        .map(|(_, name)| *name)
+        .collect();
+        if names.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", names.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_letters() {
+        let jue = Region::parse(b"JUE             ");
+        assert_eq!(jue, Region::JAPAN | Region::AMERICAS | Region::EUROPE);
+        assert_eq!(jue.to_letters(), "JUE");
+        assert_eq!(Region::parse(b"e"), Region::EUROPE);
+        assert!(Region::parse(b"E").is_pal_only());
+    }
+
+    #[test]
+    fn parses_hex_digits() {
+        assert_eq!(Region::parse(b"4"), Region::AMERICAS);
+        assert!(Region::parse(b"4").is_ntsc_only());
+        assert_eq!(Region::parse(b"F  "), Region::ALL);
+        assert_eq!(Region::ALL.to_hex(), 'F');
+    }
+
+    #[test]
+    fn blank_fields_have_no_region() {
+        assert_eq!(Region::parse(&[0; 16]), Region::NONE);
+        assert_eq!(Region::parse(b"    "), Region::NONE);
+        assert_eq!(Region::parse(b"J\0\0U"), Region::JAPAN | Region::AMERICAS);
+        assert_eq!(Region::NONE.to_string(), "none");
+    }
+}
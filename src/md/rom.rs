@@ -1,72 +1,622 @@
-use std::{str::{from_utf8_unchecked, FromStr}};
+pub use super::error::RomError;
+use super::checksum::{self, Checksum, ChecksumMode};
+use super::dat::{Dat, Match};
+use super::hash::Hashes;
+use super::console::{Console, MarsBoot};
+use super::mapper::Mapper;
+use super::{bps, ips, ups};
+use super::header::{self, Header, SramInfo, HEADER_END};
+pub use super::region::Region;
+use std::io::Write;
+use std::ops::Range;
 
-use super::rom_fmt::Format;
+use super::rom_fmt::{
+    byteswap, decode_mgd, decode_smd, deinterleave, encode_mgd, interleave, looks_byteswapped,
+    merge_even_odd, split_even_odd, Format, SmdHeader, SMD_BLOCK_SIZE,
+};
+use super::vectors::VectorTable;
 
 // Ref: https://www.zophar.net/fileuploads/2/10614uauyw/Genesis_ROM_Format.txt
 #[derive(Debug)]
 pub struct Rom {
-    pub data: Vec<u8>,
+    // Never shorter than the header: every way of changing its length
+    // re-reads the header or keeps it in place.
+    data: Vec<u8>,
     pub format: Format,
+    header: Header,
+    // Kept from SMD files so they can be written back unchanged.
+    copier_header: Option<SmdHeader>,
 }
 
-pub fn load_from_file(path: &str) -> Option<Rom> {
+// Loads a ROM, detecting its format from the contents. The extension only
+// breaks ties. Byte-swapped dumps are swapped back. SMD files without a copier
+// header load as SMD and get a fresh header when saved.
+pub fn load_from_file(path: &str) -> Result<Rom, RomError> {
     let path = std::path::Path::new(path);
-    let format = Format::from_str(path.extension()?.to_str()?).ok()?;
-    let data = std::fs::read(path).ok()?;
-    Some(Rom {
-        data: data,
-        format: format,
-    })
+    let hint = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(Format::from_extension);
+    let data = std::fs::read(path)?;
+    let detection = Format::detect_with_hint(&data, hint);
+    let mut rom = if detection.headerless {
+        let mut rom = Rom::from_bytes(deinterleave(&data), Format::Bin)?;
+        rom.format = detection.format;
+        rom
+    } else {
+        Rom::from_bytes(data, detection.format)?
+    };
+    if detection.byteswapped {
+        rom.byteswap();
+    }
+    Ok(rom)
+}
+
+// Loads a ROM from EPROM reads, one (even, odd) pair of files per bank in
+// address order.
+pub fn load_from_chip_files(banks: &[(&str, &str)]) -> Result<Rom, RomError> {
+    let mut files = Vec::new();
+    for (even, odd) in banks {
+        files.push((std::fs::read(even)?, std::fs::read(odd)?));
+    }
+    let banks: Vec<(&[u8], &[u8])> = files
+        .iter()
+        .map(|(even, odd)| (even.as_slice(), odd.as_slice()))
+        .collect();
+    Rom::from_chips(&banks)
 }
 
 impl Rom {
-    pub fn console(&self) -> &str {
-        unsafe { from_utf8_unchecked(&self.data[0x100..0x110]) }
+    // Builds a ROM from the contents of a file in the given format. Fails if
+    // the file does not decode or is too short to hold the vector table and
+    // header.
+    pub fn from_bytes(file: Vec<u8>, format: Format) -> Result<Self, RomError> {
+        let (data, copier_header) = match format {
+            Format::Bin | Format::Md => (file, None),
+            Format::Smd => {
+                let (copier_header, data) = decode_smd(&file)?;
+                (data, Some(copier_header))
+            }
+            Format::Mgd => (decode_mgd(&file)?, None),
+        };
+        let header = Header::parse(&data)?;
+        Ok(Self {
+            data,
+            format,
+            header,
+            copier_header,
+        })
+    }
+    // Builds a ROM from the contents of even/odd chip pairs, one pair per
+    // bank in address order. The even chip holds the high byte of each word.
+    pub fn from_chips(banks: &[(&[u8], &[u8])]) -> Result<Self, RomError> {
+        let mut data = Vec::new();
+        for (even, odd) in banks {
+            data.extend(merge_even_odd(even, odd)?);
+        }
+        Self::from_bytes(data, Format::Bin)
+    }
+    // Splits the ROM into (even, odd) chip images of `chip_size` bytes each,
+    // one pair per bank, for burning. The last bank is padded with 0xFF, the
+    // erased EPROM value.
+    pub fn split_chips(&self, chip_size: usize) -> Vec<(Vec<u8>, Vec<u8>)> {
+        self.data
+            .chunks(chip_size * 2)
+            .map(|bank| {
+                let (mut even, mut odd) = split_even_odd(bank);
+                even.resize(chip_size, 0xFF);
+                odd.resize(chip_size, 0xFF);
+                (even, odd)
+            })
+            .collect()
+    }
+    // Writes the ROM to a file in the given format.
+    pub fn save(&self, path: &str, format: Format) -> Result<(), RomError> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_to(&mut file, format)?;
+        Ok(file.flush()?)
+    }
+    // Writes the ROM in the given format. SMD output is padded with zeros to
+    // a whole number of blocks and reuses the copier header the ROM was
+    // loaded with if the size still matches, so unchanged files round trip
+    // exactly. MGD output is padded to an even length.
+    pub fn write_to(&self, mut out: impl Write, format: Format) -> Result<(), RomError> {
+        match format {
+            Format::Bin | Format::Md => out.write_all(&self.data)?,
+            Format::Smd => {
+                let mut data = self.data.clone();
+                data.resize(data.len().next_multiple_of(SMD_BLOCK_SIZE), 0);
+                let header = match &self.copier_header {
+                    Some(header) if header.rom_size() == data.len() => header.clone(),
+                    _ => SmdHeader::new(data.len()),
+                };
+                out.write_all(header.raw())?;
+                out.write_all(&interleave(&data))?;
+            }
+            Format::Mgd => {
+                let mut data = self.data.clone();
+                data.resize(data.len().next_multiple_of(2), 0);
+                out.write_all(&encode_mgd(&data))?;
+            }
+        }
+        Ok(())
+    }
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+    // Changes made through this are not seen by header() until
+    // reload_header() is called.
+    pub fn data_mut(&mut self) -> &mut [u8] {
+        &mut self.data
     }
-    pub fn copyright(&self) -> &str {
-        unsafe { from_utf8_unchecked(&self.data[0x110..0x120]) }
+    pub fn header(&self) -> &Header {
+        &self.header
     }
-    pub fn title(&self) -> &str {
-        unsafe { from_utf8_unchecked(&self.data[0x120..0x150]) }
+    pub fn copier_header(&self) -> Option<&SmdHeader> {
+        self.copier_header.as_ref()
     }
-    pub fn name_overseas(&self) -> &str {
-        unsafe { from_utf8_unchecked(&self.data[0x150..0x180]) }
+    pub fn reload_header(&mut self) -> Result<(), RomError> {
+        self.header = Header::parse(&self.data)?;
+        Ok(())
     }
-    pub fn product_type(&self) -> &str {
-        unsafe { from_utf8_unchecked(&self.data[0x180..0x182]) }
+    // Swaps in patched bytes and re-reads the header, putting the old bytes
+    // back if the new header cannot be read.
+    pub(super) fn replace_data(&mut self, data: Vec<u8>) -> Result<(), RomError> {
+        let old = std::mem::replace(&mut self.data, data);
+        if let Err(error) = self.reload_header() {
+            self.data = old;
+            return Err(error);
+        }
+        Ok(())
+    }
+    // Whether the image looks like a dump with the bytes of each word swapped.
+    pub fn is_byteswapped(&self) -> bool {
+        looks_byteswapped(&self.data)
+    }
+    // Swaps the bytes of every word, repairing such dumps, and re-reads the
+    // header.
+    pub fn byteswap(&mut self) {
+        byteswap(&mut self.data);
+        self.reload_header().expect("length is unchanged");
+    }
+    pub fn console(&self) -> Console {
+        Console::detect(&self.data, &self.header)
+    }
+    // The SH-2 boot table of a 32X cartridge.
+    pub fn mars_boot(&self) -> Option<MarsBoot> {
+        (self.console() == Console::Sega32X)
+            .then(|| MarsBoot::parse(&self.data))
+            .flatten()
+    }
+    // The mapper in its power on state, detected from the header and size.
+    pub fn mapper(&self) -> Mapper {
+        Mapper::detect(self)
     }
-    pub fn product_code(&self) -> &str {
-        unsafe { from_utf8_unchecked(&self.data[0x182..0x18E]) }
+    // Bytes a cartridge address reads through `mapper`, up to `len` of them
+    // and never across the end of a bank.
+    pub fn read(&self, mapper: &Mapper, address: u32, len: usize) -> Option<&[u8]> {
+        let offset = mapper.translate(address)?;
+        let contiguous = (1..len as u32)
+            .take_while(|&i| mapper.translate(address + i) == Some(offset + i as usize))
+            .count()
+            + 1;
+        Some(&self.data[offset..offset + contiguous.min(len)])
+    }
+    pub fn vectors(&self) -> VectorTable {
+        VectorTable::parse(&self.data).expect("data holds a header")
     }
     pub fn checksum(&self) -> u16 {
-        let mut chk = (self.data[0x18E] as u16) << 8;
-        chk += self.data[0x18F] as u16;
-        chk
+        self.header.checksum
     }
-    pub fn start(&self) -> usize {
-        u32::from_be_bytes(self.data[0x1A0..0x1A4].try_into()
-            .expect("error casting rom data into start address")) as usize
+    // Header setters. Text is space padded or truncated to the field width,
+    // and the header is re-read after every change.
+    pub fn set_title(&mut self, title: &str) {
+        self.set_text(header::TITLE, title);
     }
-    pub fn end(&self) -> usize {
-        u32::from_be_bytes(self.data[0x1A4..0x1A8].try_into()
-            .expect("error casting rom data into end address")) as usize
+    pub fn set_overseas_title(&mut self, title: &str) {
+        self.set_text(header::OVERSEAS_TITLE, title);
     }
-    pub fn ram_start(&self) -> usize {
-        u32::from_be_bytes(self.data[0x1A8..0x1AC].try_into()
-            .expect("error casting rom data to ram start address")) as usize
+    pub fn set_product_type(&mut self, product_type: &str) {
+        self.set_text(header::PRODUCT_TYPE, product_type);
     }
-    pub fn ram_end(&self) -> usize {
-        u32::from_be_bytes(self.data[0x1AC..0x1B0].try_into()
-            .expect("error casting rom data to ram end address")) as usize
+    pub fn set_product_code(&mut self, product_code: &str) {
+        self.set_text(header::PRODUCT_CODE, product_code);
     }
-    pub fn valid_checksum(&self) -> bool {
-        let mut sum = 0u16;
-        for i in (0x200..self.data.len()).step_by(2) {
-            let (result, _) = (self.data[i] as u16).overflowing_shl(8);
-            let result = result + self.data[i + 1] as u16;
-            let (result, _) = sum.overflowing_add(result);
-            sum = result;
+    // Keeps the single hex digit style if the field already uses it, or if
+    // the regions have no letters.
+    pub fn set_region(&mut self, region: Region) {
+        let current = &self.header.region.text;
+        let hex_style = current.len() == 1 && !matches!(current.as_str(), "J" | "U" | "E");
+        if hex_style || region.contains(Region::ASIA) {
+            self.set_text(header::REGION, &region.to_hex().to_string());
+        } else {
+            self.set_text(header::REGION, &region.to_letters());
+        }
+    }
+    // Ranges are inclusive, as stored in the header.
+    pub fn set_rom_range(&mut self, start: u32, end: u32) {
+        self.set_field(header::ROM_START, &start.to_be_bytes());
+        self.set_field(header::ROM_END, &end.to_be_bytes());
+    }
+    pub fn set_ram_range(&mut self, start: u32, end: u32) {
+        self.set_field(header::RAM_START, &start.to_be_bytes());
+        self.set_field(header::RAM_END, &end.to_be_bytes());
+    }
+    pub fn set_sram(&mut self, sram: &SramInfo) {
+        self.set_field(header::SRAM, &sram.to_bytes());
+    }
+    // Writes the checksum valid_checksum() expects and returns it.
+    pub fn fix_checksum(&mut self) -> u16 {
+        let checksum = self.compute_checksum(ChecksumMode::Auto).computed;
+        self.set_field(header::CHECKSUM, &checksum.to_be_bytes());
+        checksum
+    }
+    fn set_text(&mut self, range: Range<usize>, text: &str) {
+        let mut bytes = text.as_bytes().to_vec();
+        bytes.resize(range.len(), b' ');
+        self.set_field(range, &bytes);
+    }
+    fn set_field(&mut self, range: Range<usize>, bytes: &[u8]) {
+        self.data[range].copy_from_slice(bytes);
+        self.reload_header().expect("data holds a header");
+    }
+
+    // The run of 0x00 or 0xFF bytes the file ends with, as (fill, offset of
+    // the first fill byte). None if it ends with anything else.
+    // Fill inside the ROM range the header declares is part of the ROM and
+    // is not counted.
+    pub fn padding(&self) -> Option<(u8, usize)> {
+        let (fill, used) = padding(&self.data)?;
+        let used = used.max(self.declared_size());
+        (used < self.data.len()).then_some((fill, used))
+    }
+    // The smallest size the file is a repeat of, for overdumps that mirror a
+    // smaller ROM by halves, never below the size the header declares. None
+    // if the halves differ.
+    pub fn mirror_size(&self) -> Option<usize> {
+        let smallest = HEADER_END.max(self.declared_size());
+        let mut size = self.data.len();
+        while size.is_multiple_of(2) && size / 2 >= smallest {
+            let (low, high) = self.data[..size].split_at(size / 2);
+            if low != high {
+                break;
+            }
+            size /= 2;
+        }
+        (size < self.data.len()).then_some(size)
+    }
+    // The size without mirrors and trailing padding, rounded up to a whole
+    // word and never cutting into the header or the declared ROM range.
+    pub fn real_size(&self) -> usize {
+        let size = self.mirror_size().unwrap_or(self.data.len());
+        let used = match padding(&self.data[..size]) {
+            Some((_, used)) => used,
+            None => size,
+        };
+        used.next_multiple_of(2).clamp(HEADER_END.max(self.declared_size()), size)
+    }
+    // The size the header claims, from its ROM end address.
+    pub fn header_size(&self) -> usize {
+        self.header.rom_end as usize + 1
+    }
+    // header_size() when it lies inside the file, else 0.
+    fn declared_size(&self) -> usize {
+        Some(self.header_size())
+            .filter(|&size| size <= self.data.len())
+            .unwrap_or(0)
+    }
+    // Cuts mirrors and padding off, down to real_size(). The end address is
+    // only lowered if it would point past the new end, so the bytes a valid
+    // checksum covers are kept.
+    pub fn trim(&mut self) {
+        let size = self.real_size();
+        self.data.truncate(size);
+        if self.header_size() > size {
+            self.set_rom_range(self.header.rom_start, size as u32 - 1);
+        }
+    }
+    // Pads the ROM with `fill` up to `size` bytes and updates the header end
+    // address. Does nothing if the ROM is already that large.
+    pub fn expand_to(&mut self, size: usize, fill: u8) {
+        if size <= self.data.len() {
+            return;
         }
-        sum == self.checksum()
+        self.data.resize(size, fill);
+        self.set_rom_range(self.header.rom_start, size as u32 - 1);
+    }
+
+    // Hashes of the flat, deinterleaved image with any copier header
+    // removed, which is what DATs list.
+    pub fn hashes(&self) -> Hashes {
+        Hashes::of(&self.data)
+    }
+    // Hashes of the file as it would be written back in its own format.
+    pub fn file_hashes(&self) -> Hashes {
+        let mut file = Vec::new();
+        self.write_to(&mut file, self.format).expect("writing to memory");
+        Hashes::of(&file)
+    }
+    // Applies an IPS patch, optionally fixing the checksum afterwards.
+    pub fn apply_ips(&mut self, patch: &[u8], fix_checksum: bool) -> Result<(), RomError> {
+        ips::apply(self, patch, fix_checksum)
+    }
+    // Applies a BPS patch, rejecting it unless the ROM is the one it was made
+    // for.
+    pub fn apply_bps(&mut self, patch: &[u8]) -> Result<(), RomError> {
+        bps::apply(self, patch)
+    }
+    // Applies a UPS patch in whichever direction matches the ROM.
+    pub fn apply_ups(&mut self, patch: &[u8]) -> Result<(), RomError> {
+        ups::apply(self, patch)
+    }
+    pub fn identify<'a>(&self, dat: &'a Dat) -> Option<Match<'a>> {
+        dat.identify(&self.hashes())
+    }
+
+    // Computes the checksum over the bytes `mode` selects, next to the one
+    // stored in the header.
+    pub fn compute_checksum(&self, mode: ChecksumMode) -> Checksum {
+        checksum::compute(&self.data, self.header.rom_end, self.header.checksum, mode)
+    }
+    pub fn valid_checksum(&self) -> bool {
+        self.compute_checksum(ChecksumMode::Auto).is_valid()
     }
-}
\ No newline at end of file
+}
+
+fn padding(data: &[u8]) -> Option<(u8, usize)> {
+    let fill = *data.last()?;
+    if fill != 0x00 && fill != 0xFF {
+        return None;
+    }
+    let used = data.iter().rposition(|&byte| byte != fill).map_or(0, |i| i + 1);
+    Some((fill, used))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md::rom_fmt::SMD_HEADER_SIZE;
+
+    // Bytes that are never fill, with a console string.
+    fn image(size: usize) -> Vec<u8> {
+        let mut data: Vec<u8> = (0..size).map(|i| (i % 251) as u8 + 1).collect();
+        data[0x100..0x110].copy_from_slice(b"SEGA MEGA DRIVE ");
+        data
+    }
+
+    // A ROM whose header ends at `end`, with a valid checksum.
+    fn with_end(data: Vec<u8>, end: u32) -> Rom {
+        let mut rom = Rom::from_bytes(data, Format::Bin).unwrap();
+        rom.set_rom_range(0, end);
+        rom.fix_checksum();
+        rom
+    }
+
+    #[test]
+    fn fill_inside_the_declared_range_is_kept() {
+        let mut data = image(0x10000);
+        data[0xF000..].fill(0xFF);
+        let mut rom = with_end(data, 0xFFFF);
+        assert_eq!(rom.padding(), None);
+        assert_eq!(rom.real_size(), 0x10000);
+        rom.trim();
+        assert_eq!(rom.data().len(), 0x10000);
+        assert_eq!(rom.header_size(), 0x10000);
+        assert!(rom.valid_checksum());
+    }
+
+    #[test]
+    fn fill_past_the_declared_range_is_trimmed() {
+        let mut data = image(0x10000);
+        data[0xF000..].fill(0xFF);
+        let mut rom = with_end(data.clone(), 0xEFFF);
+        assert_eq!(rom.padding(), Some((0xFF, 0xF000)));
+        assert_eq!(rom.real_size(), 0xF000);
+        rom.trim();
+        assert_eq!(rom.data().len(), 0xF000);
+        assert!(rom.data()[HEADER_END..] == data[HEADER_END..0xF000]);
+        assert!(rom.valid_checksum());
+
+        // An end address past the file is lowered to the new end.
+        let mut rom = with_end(data, 0xFFFFF);
+        rom.trim();
+        assert_eq!(rom.data().len(), 0xF000);
+        assert_eq!(rom.header_size(), 0xF000);
+    }
+
+    #[test]
+    fn real_size_rounds_up_to_a_word() {
+        let mut data = image(0x1000);
+        data[0x801..].fill(0);
+        let rom = with_end(data, 0x7FF);
+        assert_eq!(rom.padding(), Some((0, 0x801)));
+        assert_eq!(rom.real_size(), 0x802);
+    }
+
+    #[test]
+    fn mirrors_stop_at_the_declared_size() {
+        // Overdumps repeat the whole ROM, header included.
+        let mirrored = |end: u32| {
+            let base = with_end(image(0x4000), end).data().repeat(4);
+            Rom::from_bytes(base, Format::Bin).unwrap()
+        };
+        let mut rom = mirrored(0x3FFF);
+        assert_eq!(rom.mirror_size(), Some(0x4000));
+        assert_eq!(rom.real_size(), 0x4000);
+        rom.trim();
+        assert_eq!(rom.data().len(), 0x4000);
+        assert!(rom.valid_checksum());
+
+        assert_eq!(mirrored(0x7FFF).mirror_size(), Some(0x8000));
+        let rom = Rom::from_bytes(image(0x4000), Format::Bin).unwrap();
+        assert_eq!(rom.mirror_size(), None);
+    }
+
+    #[test]
+    fn expand_to_pads_and_declares_the_fill() {
+        let mut rom = with_end(image(0x1000), 0xFFF);
+        rom.expand_to(0x800, 0xFF);
+        assert_eq!(rom.data().len(), 0x1000);
+        rom.expand_to(0x2000, 0xFF);
+        assert_eq!(rom.data().len(), 0x2000);
+        assert!(rom.data()[0x1000..].iter().all(|&byte| byte == 0xFF));
+        assert_eq!(rom.header_size(), 0x2000);
+        assert_eq!(rom.padding(), None);
+    }
+
+    fn written(rom: &Rom, format: Format) -> Vec<u8> {
+        let mut file = Vec::new();
+        rom.write_to(&mut file, format).unwrap();
+        file
+    }
+
+    #[test]
+    fn formats_round_trip() {
+        let rom = Rom::from_bytes(image(0x9000), Format::Bin).unwrap();
+        assert_eq!(written(&rom, Format::Bin), rom.data());
+        assert_eq!(written(&rom, Format::Md), rom.data());
+
+        // SMD pads to whole blocks and says how many there are.
+        let smd = written(&rom, Format::Smd);
+        assert_eq!(smd.len(), SMD_HEADER_SIZE + 3 * SMD_BLOCK_SIZE);
+        let back = Rom::from_bytes(smd, Format::Smd).unwrap();
+        assert_eq!(back.copier_header().unwrap().blocks(), 3);
+        assert_eq!(back.data()[..0x9000], rom.data()[..]);
+        assert!(back.data()[0x9000..].iter().all(|&byte| byte == 0));
+
+        let mgd = written(&rom, Format::Mgd);
+        assert_eq!(mgd.len(), 0x9000);
+        let back = Rom::from_bytes(mgd, Format::Mgd).unwrap();
+        assert_eq!(back.data(), rom.data());
+    }
+
+    #[test]
+    fn smd_copier_header_is_reused_while_the_size_matches() {
+        let data = image(2 * SMD_BLOCK_SIZE);
+        let mut header = SmdHeader::new(data.len()).raw().to_vec();
+        // Copiers leave their own bytes past the signature.
+        header[0x20..0x24].copy_from_slice(b"SMDX");
+        let file = [header, interleave(&data)].concat();
+        let mut rom = Rom::from_bytes(file.clone(), Format::Smd).unwrap();
+        assert_eq!(written(&rom, Format::Smd), file);
+
+        rom.expand_to(3 * SMD_BLOCK_SIZE, 0xFF);
+        let grown = written(&rom, Format::Smd);
+        assert_eq!(
+            grown[..SMD_HEADER_SIZE],
+            *SmdHeader::new(3 * SMD_BLOCK_SIZE).raw()
+        );
+    }
+
+    #[test]
+    fn large_smd_block_count_saturates() {
+        let rom = Rom::from_bytes(image(0x40_0000), Format::Bin).unwrap();
+        let smd = written(&rom, Format::Smd);
+        assert_eq!(smd[0], 0xFF);
+        let back = Rom::from_bytes(smd, Format::Smd).unwrap();
+        assert_eq!(back.data(), rom.data());
+    }
+
+    #[test]
+    fn save_writes_the_file() {
+        let rom = Rom::from_bytes(image(0x4000), Format::Bin).unwrap();
+        let name = format!("genesis-rs-save-{}.smd", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        let path = path.to_str().unwrap();
+        rom.save(path, Format::Smd).unwrap();
+        let file = std::fs::read(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(file, written(&rom, Format::Smd));
+    }
+
+    #[test]
+    fn setters_write_the_header() {
+        let mut rom = Rom::from_bytes(image(0x1000), Format::Bin).unwrap();
+        rom.set_title("SONIC");
+        assert_eq!(
+            rom.data()[header::TITLE.start..header::TITLE.start + 6],
+            *b"SONIC "
+        );
+        assert!(rom.data()[header::TITLE].ends_with(b"    "));
+        assert_eq!(rom.header().title.text, "SONIC");
+
+        // Long text is cut at the field width; nothing after it changes.
+        let next = rom.data()[header::PRODUCT_TYPE].to_vec();
+        rom.set_overseas_title(&"X".repeat(60));
+        assert_eq!(rom.header().overseas_title.text, "X".repeat(48));
+        assert_eq!(rom.data()[header::PRODUCT_TYPE], next);
+
+        rom.set_product_type("GMX");
+        rom.set_product_code("00001009-00");
+        assert_eq!(rom.data()[header::PRODUCT_TYPE], *b"GM");
+        assert_eq!(rom.data()[header::PRODUCT_CODE], *b"00001009-00 ");
+
+        rom.set_rom_range(0, 0xFFF);
+        rom.set_ram_range(0xFF_0000, 0xFF_FFFF);
+        assert_eq!(rom.data()[header::ROM_END], [0, 0, 0x0F, 0xFF]);
+        assert_eq!(rom.data()[header::RAM_START], [0, 0xFF, 0, 0]);
+        assert_eq!(
+            (rom.header().ram_start, rom.header().ram_end),
+            (0xFF_0000, 0xFF_FFFF)
+        );
+
+        let sram = SramInfo {
+            present: true,
+            lanes: header::SramLanes::Odd,
+            backup: true,
+            start: 0x20_0001,
+            end: 0x20_3FFF,
+        };
+        rom.set_sram(&sram);
+        assert_eq!(rom.data()[header::SRAM], sram.to_bytes());
+        assert_eq!(rom.header().sram_info(), sram);
+    }
+
+    #[test]
+    fn region_keeps_the_field_style() {
+        let mut rom = Rom::from_bytes(image(0x1000), Format::Bin).unwrap();
+        rom.set_region(Region::JAPAN | Region::EUROPE);
+        assert_eq!(rom.data()[header::REGION], *b"JE ");
+        // Asia has no letter, so the digit is used.
+        rom.set_region(Region::ASIA | Region::JAPAN);
+        assert_eq!(rom.data()[header::REGION], *b"3  ");
+        // Once digits, always digits.
+        rom.set_region(Region::AMERICAS);
+        assert_eq!(rom.data()[header::REGION], *b"4  ");
+        assert_eq!(rom.header().regions(), Region::AMERICAS);
+    }
+
+    #[test]
+    fn fix_checksum_makes_it_valid() {
+        let mut rom = Rom::from_bytes(image(0x1000), Format::Bin).unwrap();
+        rom.set_rom_range(0, 0xFFF);
+        assert!(!rom.valid_checksum());
+        let checksum = rom.fix_checksum();
+        assert!(rom.valid_checksum());
+        assert_eq!(rom.checksum(), checksum);
+        assert_eq!(rom.data()[header::CHECKSUM], checksum.to_be_bytes());
+
+        // The header is not summed, the code after it is.
+        rom.set_title("CHANGED");
+        assert!(rom.valid_checksum());
+        rom.data_mut()[0x300] ^= 0xFF;
+        assert!(!rom.valid_checksum());
+        rom.fix_checksum();
+        assert!(rom.valid_checksum());
+    }
+
+    #[test]
+    fn headerless_smd_files_load() {
+        let rom = Rom::from_bytes(image(2 * SMD_BLOCK_SIZE), Format::Bin).unwrap();
+        let name = format!("genesis-rs-load-{}.bin", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        let path = path.to_str().unwrap();
+        std::fs::write(path, interleave(rom.data())).unwrap();
+        let loaded = load_from_file(path);
+        std::fs::remove_file(path).unwrap();
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.format, Format::Smd);
+        assert_eq!(loaded.data(), rom.data());
+        assert!(loaded.copier_header().is_none());
+    }
+}
@@ -1,31 +1,426 @@
-use std::{str::FromStr, fmt::Display};
+use std::{cmp::Reverse, str::FromStr, fmt::Display};
 
-#[derive(Debug)]
+use super::error::RomError;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum Format {
     Bin,
     Smd,
     Md,
+    Mgd,
 }
 
 impl FromStr for Format {
-    type Err = &'static str;
+    type Err = RomError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "bin" => Ok(Self::Bin),
             "smd" => Ok(Self::Smd),
             "md" => Ok(Self::Md),
-            _ => Err("invalid or unknown format"),
+            "mgd" => Ok(Self::Mgd),
+            _ => Err(RomError::UnknownFormat(s.to_string())),
         }
     }
 }
 
+// A possible reading of a file, with how sure detection is about it out of
+// 100.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Detection {
+    pub format: Format,
+    pub confidence: u8,
+    // The image has the bytes of every word swapped.
+    pub byteswapped: bool,
+    // SMD blocks with no copier header in front of them.
+    pub headerless: bool,
+}
+
+impl Format {
+    // Maps a file extension, in any case, to the format usually found under
+    // it.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "bin" | "gen" | "68k" | "sgd" => Some(Self::Bin),
+            "smd" => Some(Self::Smd),
+            "md" => Some(Self::Md),
+            "mgd" => Some(Self::Mgd),
+            _ => None,
+        }
+    }
+
+    // Every plausible reading of a file, most likely first. Bin and Md have
+    // the same layout, so flat images are reported as Bin.
+    pub fn detect(file: &[u8]) -> Vec<Detection> {
+        let mut guesses = Vec::new();
+        if SmdHeader::parse(file).is_ok() {
+            let body = &file[SMD_HEADER_SIZE..];
+            let prefix = deinterleave(&body[..body.len().min(SMD_BLOCK_SIZE)]);
+            guesses.push(Detection {
+                format: Self::Smd,
+                confidence: 60 + score_flat(&prefix, body.len()) / 3,
+                byteswapped: false,
+                headerless: false,
+            });
+        } else if file.len() >= SMD_BLOCK_SIZE && file.len().is_multiple_of(SMD_BLOCK_SIZE) {
+            // Copier headers get stripped; the blocks give themselves away
+            // once the first one is put back together.
+            let score = score_flat(&deinterleave(&file[..SMD_BLOCK_SIZE]), file.len());
+            if score >= 40 {
+                guesses.push(Detection {
+                    format: Self::Smd,
+                    confidence: score - 5,
+                    byteswapped: false,
+                    headerless: true,
+                });
+            }
+        }
+        if file.len().is_multiple_of(2) {
+            // MGD has no header, so only trust it when it yields one.
+            let half = file.len() / 2;
+            let end = half.min(0x100);
+            let prefix = merge_even_odd(&file[half..half + end], &file[..end]).unwrap();
+            let score = score_flat(&prefix, file.len());
+            if score >= 80 {
+                guesses.push(Detection {
+                    format: Self::Mgd,
+                    confidence: score - 5,
+                    byteswapped: false,
+                    headerless: false,
+                });
+            }
+        }
+        let score = score_flat(file, file.len());
+        guesses.push(Detection {
+            format: Self::Bin,
+            confidence: score,
+            byteswapped: false,
+            headerless: false,
+        });
+        let swapped = score_swapped(file);
+        if swapped > score {
+            guesses.push(Detection {
+                format: Self::Bin,
+                confidence: swapped - 5,
+                byteswapped: true,
+                headerless: false,
+            });
+        }
+        guesses.sort_by_key(|guess| Reverse(guess.confidence));
+        guesses
+    }
+
+    // The most likely reading, with `hint` (usually from the extension)
+    // breaking ties and choosing between Bin and Md for flat images.
+    pub fn detect_with_hint(file: &[u8], hint: Option<Format>) -> Detection {
+        let flat = |format| matches!(format, Self::Bin | Self::Md);
+        let mut guesses = Self::detect(file);
+        for guess in &mut guesses {
+            match hint {
+                Some(hint) if hint == guess.format || (flat(hint) && flat(guess.format)) => {
+                    guess.format = hint;
+                    guess.confidence = (guess.confidence + 5).min(100);
+                }
+                _ => {}
+            }
+        }
+        guesses.sort_by_key(|guess| Reverse(guess.confidence));
+        guesses[0]
+    }
+}
+
+// How much the start of a flat image looks like a Mega Drive ROM of
+// `size` bytes: "SEGA" at 0x100 is near certain, sane reset vectors are
+// a weak sign.
+fn score_flat(data: &[u8], size: usize) -> u8 {
+    let Some(console) = data.get(0x100..0x110) else {
+        return 0;
+    };
+    if console.starts_with(b"SEGA") {
+        return 90;
+    }
+    if console.windows(4).any(|w| w == b"SEGA") {
+        return 80;
+    }
+    let long = |at: usize| u32::from_be_bytes(data[at..at + 4].try_into().unwrap());
+    let (ssp, pc) = (long(0), long(4));
+    if ssp.is_multiple_of(2) && pc.is_multiple_of(2) && pc >= 0x200 && (pc as usize) < size {
+        return 40;
+    }
+    10
+}
+
+fn score_swapped(data: &[u8]) -> u8 {
+    score_flat(&swap_words(&data[..data.len().min(0x200)]), data.len())
+}
+
+// Whether a flat image reads better with the bytes of each word swapped:
+// "ESAG" at 0x100, or vectors that only make sense swapped.
+pub fn looks_byteswapped(data: &[u8]) -> bool {
+    score_swapped(data) > score_flat(data, data.len())
+}
+
+// A copy with the two bytes of every word exchanged.
+pub fn swap_words(data: &[u8]) -> Vec<u8> {
+    let mut swapped = data.to_vec();
+    byteswap(&mut swapped);
+    swapped
+}
+
+// Exchanges the two bytes of every word in place. A trailing odd byte is left
+// alone.
+pub fn byteswap(data: &mut [u8]) {
+    for word in data.chunks_exact_mut(2) {
+        word.swap(0, 1);
+    }
+}
+
 impl Display for Format {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", match self {
             Self::Bin => "bin",
             Self::Smd => "smd",
             Self::Md  => "md",
+            Self::Mgd => "mgd",
+        })
+    }
+}
+
+// Super Magic Drive copier files: a 512 byte header, then the ROM in 16 KB
+// blocks, each holding the odd bytes of the block followed by the even ones.
+// Ref: https://segaretro.org/Super_Magic_Drive
+pub const SMD_HEADER_SIZE: usize = 512;
+pub const SMD_BLOCK_SIZE: usize = 0x4000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SmdHeader {
+    raw: [u8; SMD_HEADER_SIZE],
+    // Size of the body the header came with, in bytes.
+    rom_size: usize,
+}
+
+impl SmdHeader {
+    // A header for a ROM of `rom_size` bytes, as the copier writes it. The
+    // block count is a single byte, so ROMs of 4 MB and up store 255 rather
+    // than letting it wrap to a small number.
+    pub fn new(rom_size: usize) -> Self {
+        let mut raw = [0; SMD_HEADER_SIZE];
+        raw[0] = rom_size.div_ceil(SMD_BLOCK_SIZE).min(0xFF) as u8;
+        raw[1] = 0x03;
+        raw[8] = 0xAA;
+        raw[9] = 0xBB;
+        raw[10] = 0x06;
+        Self { raw, rom_size }
+    }
+    // Reads and checks the header at the start of an SMD file. The block
+    // count is not checked against the file size since many tools leave it
+    // zero or let it overflow.
+    pub fn parse(file: &[u8]) -> Result<Self, RomError> {
+        let raw: [u8; SMD_HEADER_SIZE] = file
+            .get(..SMD_HEADER_SIZE)
+            .ok_or(RomError::TooShort {
+                len: file.len(),
+                needed: SMD_HEADER_SIZE,
+            })?
+            .try_into()
+            .unwrap();
+        if raw[8] != 0xAA || raw[9] != 0xBB {
+            return Err(RomError::BadCopierHeader("missing signature"));
+        }
+        if raw[10] != 0x06 {
+            return Err(RomError::BadCopierHeader("not a Mega Drive program"));
+        }
+        if !(file.len() - SMD_HEADER_SIZE).is_multiple_of(SMD_BLOCK_SIZE) {
+            return Err(RomError::BadCopierHeader("body is not a whole number of blocks"));
+        }
+        Ok(Self {
+            raw,
+            rom_size: file.len() - SMD_HEADER_SIZE,
         })
     }
+    pub fn raw(&self) -> &[u8] {
+        &self.raw
+    }
+    pub fn rom_size(&self) -> usize {
+        self.rom_size
+    }
+    // Number of 16 KB blocks, as stored.
+    pub fn blocks(&self) -> u8 {
+        self.raw[0]
+    }
+    // Whether this file is one part of a ROM split across several files.
+    pub fn is_split(&self) -> bool {
+        self.raw[2] & 0x40 != 0
+    }
+}
+
+// Splits an SMD file into its copier header and the flat ROM.
+pub fn decode_smd(file: &[u8]) -> Result<(SmdHeader, Vec<u8>), RomError> {
+    let header = SmdHeader::parse(file)?;
+    Ok((header, deinterleave(&file[SMD_HEADER_SIZE..])))
+}
+
+// Turns SMD blocks back into a flat ROM. A trailing partial block is
+// de-interleaved as if it were padded to full size.
+pub fn deinterleave(blocks: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(blocks.len());
+    for block in blocks.chunks(SMD_BLOCK_SIZE) {
+        let (odd, even) = block.split_at(block.len() / 2);
+        for (&even, &odd) in even.iter().zip(odd) {
+            data.push(even);
+            data.push(odd);
+        }
+    }
+    data
+}
+
+// The inverse of deinterleave. The ROM must be a whole number of blocks.
+pub fn interleave(data: &[u8]) -> Vec<u8> {
+    let mut blocks = Vec::with_capacity(data.len());
+    for block in data.chunks(SMD_BLOCK_SIZE) {
+        blocks.extend(block.iter().skip(1).step_by(2));
+        blocks.extend(block.iter().step_by(2));
+    }
+    blocks
+}
+
+// Multi Game Doctor files have no header and hold the whole ROM as one
+// block: every odd byte, then every even byte.
+pub fn decode_mgd(file: &[u8]) -> Result<Vec<u8>, RomError> {
+    if !file.len().is_multiple_of(2) {
+        return Err(RomError::OddLength { len: file.len() });
+    }
+    let (odd, even) = file.split_at(file.len() / 2);
+    merge_even_odd(even, odd)
+}
+
+pub fn encode_mgd(data: &[u8]) -> Vec<u8> {
+    let (even, odd) = split_even_odd(data);
+    [odd, even].concat()
+}
+
+// Interleaves the contents of an even and an odd chip. The even chip holds
+// the high byte of each word (D15-D8), the odd chip the low byte.
+pub fn merge_even_odd(even: &[u8], odd: &[u8]) -> Result<Vec<u8>, RomError> {
+    if even.len() != odd.len() {
+        return Err(RomError::ChipSizeMismatch {
+            even: even.len(),
+            odd: odd.len(),
+        });
+    }
+    Ok(even.iter().zip(odd).flat_map(|(&even, &odd)| [even, odd]).collect())
+}
+
+// The inverse of merge_even_odd. A trailing odd byte goes to the even chip.
+pub fn split_even_odd(data: &[u8]) -> (Vec<u8>, Vec<u8>) {
+    let even = data.iter().step_by(2).copied().collect();
+    let odd = data.iter().skip(1).step_by(2).copied().collect();
+    (even, odd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Four SMD blocks with a console string and sane vectors.
+    fn rom() -> Vec<u8> {
+        let mut data: Vec<u8> = (0..4 * SMD_BLOCK_SIZE)
+            .map(|i| (i * 7 + i / 251) as u8)
+            .collect();
+        data[..8].copy_from_slice(&[0x00, 0xFF, 0xFE, 0x00, 0x00, 0x00, 0x02, 0x00]);
+        data[0x100..0x110].copy_from_slice(b"SEGA MEGA DRIVE ");
+        data
+    }
+
+    #[test]
+    fn smd_round_trip() {
+        let data = rom();
+        let blocks = interleave(&data);
+        // Odd bytes first in every block.
+        assert_eq!(blocks[0], data[1]);
+        assert_eq!(blocks[SMD_BLOCK_SIZE / 2], data[0]);
+        assert_eq!(deinterleave(&blocks), data);
+
+        let file = [SmdHeader::new(data.len()).raw(), &blocks].concat();
+        let (header, decoded) = decode_smd(&file).unwrap();
+        assert_eq!(header.blocks(), 4);
+        assert_eq!(header.rom_size(), data.len());
+        assert_eq!(decoded, data);
+        assert_eq!(Format::detect(&file)[0].format, Format::Smd);
+        assert!(!Format::detect(&file)[0].headerless);
+    }
+
+    #[test]
+    fn headerless_smd_is_detected() {
+        let data = rom();
+        let best = Format::detect(&interleave(&data))[0];
+        assert_eq!((best.format, best.headerless), (Format::Smd, true));
+        // Flat images that happen to be whole blocks stay flat.
+        let best = Format::detect(&data)[0];
+        assert_eq!((best.format, best.headerless), (Format::Bin, false));
+    }
+
+    #[test]
+    fn smd_header_is_checked() {
+        let mut file = [
+            SmdHeader::new(SMD_BLOCK_SIZE).raw(),
+            &[0; SMD_BLOCK_SIZE][..],
+        ]
+        .concat();
+        assert_eq!(SmdHeader::new(0x3F_C001).blocks(), 0xFF);
+        assert_eq!(SmdHeader::new(0x40_0000).blocks(), 0xFF);
+        assert_eq!(SmdHeader::new(0x50_0000).blocks(), 0xFF);
+        file[8] = 0;
+        assert!(matches!(
+            SmdHeader::parse(&file),
+            Err(RomError::BadCopierHeader(_))
+        ));
+        assert!(matches!(
+            SmdHeader::parse(&file[..100]),
+            Err(RomError::TooShort { .. })
+        ));
+    }
+
+    #[test]
+    fn byteswapped_dumps_are_detected() {
+        let data = rom();
+        let swapped = swap_words(&data);
+        assert_eq!(&swapped[0x100..0x104], b"ESAG");
+        assert!(looks_byteswapped(&swapped));
+        assert!(!looks_byteswapped(&data));
+        let best = Format::detect(&swapped)[0];
+        assert_eq!((best.format, best.byteswapped), (Format::Bin, true));
+
+        let mut odd = vec![1, 2, 3];
+        byteswap(&mut odd);
+        assert_eq!(odd, [2, 1, 3]);
+    }
+
+    #[test]
+    fn mgd_round_trip() {
+        let data = rom();
+        let file = encode_mgd(&data);
+        // Odd bytes, then even bytes.
+        assert_eq!(file[0], data[1]);
+        assert_eq!(file[data.len() / 2], data[0]);
+        assert_eq!(decode_mgd(&file).unwrap(), data);
+        assert_eq!(Format::detect(&file)[0].format, Format::Mgd);
+        assert!(matches!(
+            decode_mgd(&file[1..]),
+            Err(RomError::OddLength { .. })
+        ));
+    }
+
+    #[test]
+    fn chips_round_trip() {
+        let data = rom();
+        let (even, odd) = split_even_odd(&data);
+        assert_eq!(even[0], data[0]);
+        assert_eq!(odd[0], data[1]);
+        assert_eq!(merge_even_odd(&even, &odd).unwrap(), data);
+        assert!(matches!(
+            merge_even_odd(&even, &odd[1..]),
+            Err(RomError::ChipSizeMismatch { .. })
+        ));
+    }
 }
@@ -0,0 +1,310 @@
+use std::fmt::Display;
+
+use super::error::RomError;
+use super::header::{SramInfo, SramLanes};
+use super::rom::Rom;
+
+// Serial EEPROMs some games save to instead of SRAM.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EepromChip {
+    X24C01,
+    X24C02,
+    X24C04,
+    X24C08,
+    X24C16,
+    X24C65,
+}
+
+impl EepromChip {
+    pub fn size(&self) -> usize {
+        match self {
+            Self::X24C01 => 128,
+            Self::X24C02 => 256,
+            Self::X24C04 => 512,
+            Self::X24C08 => 1024,
+            Self::X24C16 => 2048,
+            Self::X24C65 => 8192,
+        }
+    }
+}
+
+impl Display for EepromChip {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Self::X24C01 => "24C01",
+            Self::X24C02 => "24C02",
+            Self::X24C04 => "24C04",
+            Self::X24C08 => "24C08",
+            Self::X24C16 => "24C16",
+            Self::X24C65 => "24C65",
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct EepromGame {
+    pub product_code: &'static str,
+    pub name: &'static str,
+    pub chip: EepromChip,
+}
+
+// Games known to save to an I2C EEPROM. Their headers rarely say so.
+// Ref: Genesis Plus GX, eeprom_i2c.c
+pub static EEPROM_GAMES: [EepromGame; 23] = [
+    EepromGame { product_code: "T-50176", name: "Rings of Power", chip: EepromChip::X24C01 },
+    EepromGame { product_code: "T-50396", name: "NHLPA Hockey 93", chip: EepromChip::X24C01 },
+    EepromGame { product_code: "T-50446", name: "John Madden Football 93", chip: EepromChip::X24C01 },
+    EepromGame { product_code: "T-50516", name: "John Madden Football 93 Championship Edition", chip: EepromChip::X24C01 },
+    EepromGame { product_code: "T-50606", name: "Bill Walsh College Football", chip: EepromChip::X24C01 },
+    EepromGame { product_code: "T-12046", name: "Mega Man - The Wily Wars", chip: EepromChip::X24C01 },
+    EepromGame { product_code: "T-12053", name: "Rockman Mega World", chip: EepromChip::X24C01 },
+    EepromGame { product_code: "MK-1215", name: "Evander Holyfield's Real Deal Boxing", chip: EepromChip::X24C01 },
+    EepromGame { product_code: "MK-1228", name: "Greatest Heavyweights", chip: EepromChip::X24C01 },
+    EepromGame { product_code: "G-5538", name: "Greatest Heavyweights (Japan)", chip: EepromChip::X24C01 },
+    EepromGame { product_code: "PR-1993", name: "Greatest Heavyweights (Europe)", chip: EepromChip::X24C01 },
+    EepromGame { product_code: "G-4060", name: "Wonder Boy in Monster World", chip: EepromChip::X24C01 },
+    EepromGame { product_code: "00001211", name: "Sports Talk Baseball", chip: EepromChip::X24C01 },
+    EepromGame { product_code: "00004076", name: "Honoo no Toukyuuji Dodge Danpei", chip: EepromChip::X24C01 },
+    EepromGame { product_code: "G-4524", name: "Ninja Burai Densetsu", chip: EepromChip::X24C01 },
+    EepromGame { product_code: "00054503", name: "Game Toshokan", chip: EepromChip::X24C01 },
+    EepromGame { product_code: "T-81033", name: "NBA Jam (Japan)", chip: EepromChip::X24C02 },
+    EepromGame { product_code: "T-081326", name: "NBA Jam", chip: EepromChip::X24C02 },
+    EepromGame { product_code: "T-81406", name: "NBA Jam Tournament Edition", chip: EepromChip::X24C04 },
+    EepromGame { product_code: "T-081276", name: "NFL Quarterback Club", chip: EepromChip::X24C02 },
+    EepromGame { product_code: "T-081586", name: "NFL Quarterback Club 96", chip: EepromChip::X24C16 },
+    EepromGame { product_code: "T-81576", name: "College Slam", chip: EepromChip::X24C16 },
+    EepromGame { product_code: "T-81476", name: "Frank Thomas Big Hurt Baseball", chip: EepromChip::X24C16 },
+];
+
+// Looks a header product code up by its serial, ignoring the revision
+// suffix in forms like "T-50176 -00" and "00001211-00".
+pub fn find_eeprom(product_code: &str) -> Option<&'static EepromGame> {
+    let code = product_code.trim();
+    EEPROM_GAMES.iter().find(|game| {
+        code.strip_prefix(game.product_code)
+            .is_some_and(|rest| !rest.starts_with(|c: char| c.is_ascii_alphanumeric()))
+    })
+}
+
+#[derive(Clone, Debug)]
+pub enum SaveKind {
+    Sram(SramInfo),
+    Eeprom(&'static EepromGame),
+}
+
+// A game's battery backed memory. The contents are kept linear: for SRAM on
+// one byte lane only the bytes that exist, in address order.
+#[derive(Clone, Debug)]
+pub struct SaveMemory {
+    pub kind: SaveKind,
+    data: Vec<u8>,
+}
+
+impl SaveMemory {
+    // Blank save memory for a ROM, from the EEPROM database or else the
+    // header SRAM block. Fails if the game has neither, or if the SRAM block
+    // is reversed or larger than MAX_SRAM_SIZE.
+    pub fn for_rom(rom: &Rom) -> Result<Self, RomError> {
+        let header = rom.header();
+        let kind = match find_eeprom(&header.product_code.text) {
+            Some(game) => SaveKind::Eeprom(game),
+            None => {
+                let sram = header.sram_info();
+                if !sram.present {
+                    return Err(RomError::NoSaveMemory);
+                }
+                if sram.end < sram.start || bus_size(&sram) > MAX_SRAM_SIZE {
+                    return Err(RomError::BadSramRange {
+                        start: sram.start,
+                        end: sram.end,
+                    });
+                }
+                SaveKind::Sram(sram)
+            }
+        };
+        let data = vec![0xFF; linear_size(&kind)];
+        Ok(Self { kind, data })
+    }
+    // Reads an .srm file for a ROM.
+    pub fn load(rom: &Rom, path: &str) -> Result<Self, RomError> {
+        let mut save = Self::for_rom(rom)?;
+        save.read_srm(&std::fs::read(path)?);
+        Ok(save)
+    }
+    // Takes the contents of an .srm file. A file the size of the SRAM's bus
+    // range is in bus order, with the unused lane of odd or even SRAM
+    // present; anything else is taken as linear and padded or cut to size.
+    pub fn read_srm(&mut self, file: &[u8]) {
+        match &self.kind {
+            SaveKind::Sram(sram) if file.len() == bus_size(sram) && sram.lanes != SramLanes::Both => {
+                let lane = (sram.lanes == SramLanes::Odd) as usize;
+                self.data = file.iter().skip(lane).step_by(2).copied().collect();
+            }
+            _ => {
+                self.data = file.to_vec();
+                self.data.resize(linear_size(&self.kind), 0xFF);
+            }
+        }
+    }
+    // The .srm file contents: SRAM in bus order with the unused lane set to
+    // 0xFF, EEPROM as the raw chip contents.
+    pub fn to_srm(&self) -> Vec<u8> {
+        match &self.kind {
+            SaveKind::Sram(sram) if sram.lanes != SramLanes::Both => {
+                let odd = sram.lanes == SramLanes::Odd;
+                self.data
+                    .iter()
+                    .flat_map(|&byte| if odd { [0xFF, byte] } else { [byte, 0xFF] })
+                    .collect()
+            }
+            _ => self.data.clone(),
+        }
+    }
+    pub fn save(&self, path: &str) -> Result<(), RomError> {
+        Ok(std::fs::write(path, self.to_srm())?)
+    }
+    pub fn linear(&self) -> &[u8] {
+        &self.data
+    }
+    pub fn linear_mut(&mut self) -> &mut [u8] {
+        &mut self.data
+    }
+    // The linear offset of a 68k address, if it is a byte of this SRAM.
+    pub fn offset_of(&self, address: u32) -> Option<usize> {
+        let SaveKind::Sram(sram) = &self.kind else {
+            return None;
+        };
+        if !(sram.start..=sram.end).contains(&address) {
+            return None;
+        }
+        let offset = (address - (sram.start & !1)) as usize;
+        match sram.lanes {
+            SramLanes::Both => Some((address - sram.start) as usize),
+            SramLanes::Even if offset.is_multiple_of(2) => Some(offset / 2),
+            SramLanes::Odd if !offset.is_multiple_of(2) => Some(offset / 2),
+            _ => None,
+        }
+    }
+}
+
+// The most bus address space SRAM is taken to span. Cartridges carry at
+// most 32 KB on one lane or 64 KB on both; larger ranges are corrupt headers.
+const MAX_SRAM_SIZE: usize = 0x1_0000;
+
+// Bytes of bus address space the SRAM spans, whole words.
+fn bus_size(sram: &SramInfo) -> usize {
+    ((sram.end | 1) - (sram.start & !1)) as usize + 1
+}
+
+fn linear_size(kind: &SaveKind) -> usize {
+    match kind {
+        SaveKind::Sram(sram) if sram.lanes == SramLanes::Both => (sram.end - sram.start) as usize + 1,
+        SaveKind::Sram(sram) => bus_size(sram) / 2,
+        SaveKind::Eeprom(game) => game.chip.size(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::md::rom_fmt::Format;
+
+    fn rom_with_sram(lanes: SramLanes, start: u32, end: u32) -> Rom {
+        let mut data = vec![0; 0x400];
+        data[0x100..0x104].copy_from_slice(b"SEGA");
+        let mut rom = Rom::from_bytes(data, Format::Bin).unwrap();
+        rom.set_sram(&SramInfo {
+            present: true,
+            lanes,
+            backup: true,
+            start,
+            end,
+        });
+        rom
+    }
+
+    #[test]
+    fn odd_lane_sram() {
+        let rom = rom_with_sram(SramLanes::Odd, 0x200001, 0x203FFF);
+        let mut save = SaveMemory::for_rom(&rom).unwrap();
+        assert_eq!(save.linear().len(), 0x2000);
+        assert_eq!(save.offset_of(0x200001), Some(0));
+        assert_eq!(save.offset_of(0x200003), Some(1));
+        assert_eq!(save.offset_of(0x200002), None);
+        assert_eq!(save.offset_of(0x204001), None);
+
+        save.linear_mut()[..2].copy_from_slice(&[0x12, 0x34]);
+        let srm = save.to_srm();
+        assert_eq!(srm.len(), 0x4000);
+        assert_eq!(srm[..4], [0xFF, 0x12, 0xFF, 0x34]);
+        let mut loaded = SaveMemory::for_rom(&rom).unwrap();
+        loaded.read_srm(&srm);
+        assert_eq!(loaded.linear(), save.linear());
+        // Linear files are taken as they are.
+        loaded.read_srm(&[0x56]);
+        assert_eq!(loaded.linear()[..2], [0x56, 0xFF]);
+    }
+
+    #[test]
+    fn even_lane_sram() {
+        let rom = rom_with_sram(SramLanes::Even, 0x200000, 0x203FFE);
+        let mut save = SaveMemory::for_rom(&rom).unwrap();
+        assert_eq!(save.linear().len(), 0x2000);
+        assert_eq!(save.offset_of(0x200000), Some(0));
+        assert_eq!(save.offset_of(0x200002), Some(1));
+        assert_eq!(save.offset_of(0x200001), None);
+
+        save.linear_mut()[1] = 0x34;
+        let srm = save.to_srm();
+        assert_eq!(srm[..4], [0xFF, 0xFF, 0x34, 0xFF]);
+        let mut loaded = SaveMemory::for_rom(&rom).unwrap();
+        loaded.read_srm(&srm);
+        assert_eq!(loaded.linear(), save.linear());
+    }
+
+    #[test]
+    fn word_wide_sram() {
+        let rom = rom_with_sram(SramLanes::Both, 0x200000, 0x20FFFF);
+        let mut save = SaveMemory::for_rom(&rom).unwrap();
+        assert_eq!(save.linear().len(), 0x10000);
+        assert_eq!(save.offset_of(0x200011), Some(0x11));
+        save.linear_mut()[0x11] = 0x42;
+        let srm = save.to_srm();
+        assert_eq!(srm, save.linear());
+        let mut loaded = SaveMemory::for_rom(&rom).unwrap();
+        loaded.read_srm(&srm);
+        assert_eq!(loaded.linear(), save.linear());
+    }
+
+    #[test]
+    fn bad_sram_ranges_are_rejected() {
+        for (start, end) in [(0x200001, 0xFFFF_FFFF), (0x203FFF, 0x200001)] {
+            let rom = rom_with_sram(SramLanes::Odd, start, end);
+            assert!(matches!(
+                SaveMemory::for_rom(&rom),
+                Err(RomError::BadSramRange { .. })
+            ));
+        }
+        let mut rom = rom_with_sram(SramLanes::Both, 0x200000, 0x20FFFF);
+        rom.set_sram(&SramInfo::parse(&[b' '; 12]));
+        assert!(matches!(
+            SaveMemory::for_rom(&rom),
+            Err(RomError::NoSaveMemory)
+        ));
+    }
+
+    #[test]
+    fn eeprom_games_are_found_by_serial() {
+        assert_eq!(find_eeprom("T-50176 -00").unwrap().name, "Rings of Power");
+        assert_eq!(find_eeprom("00001211-00").unwrap().chip, EepromChip::X24C01);
+        assert_eq!(find_eeprom(" T-81406 ").unwrap().chip, EepromChip::X24C04);
+        assert!(find_eeprom("T-501760").is_none());
+        assert!(find_eeprom("").is_none());
+
+        let mut rom = rom_with_sram(SramLanes::Odd, 0x200001, 0x203FFF);
+        rom.set_product_code("T-081586 -00");
+        let save = SaveMemory::for_rom(&rom).unwrap();
+        assert!(matches!(save.kind, SaveKind::Eeprom(_)));
+        assert_eq!(save.linear().len(), 2048);
+    }
+}
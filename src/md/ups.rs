@@ -0,0 +1,160 @@
+use super::error::RomError;
+use super::hash::crc32;
+use super::patch_util::{check_crc, split_footer, write_number, Reader};
+use super::rom::Rom;
+
+// UPS patches: "UPS1", source and target sizes, hunks of XOR bytes each
+// after a skip count and ending in a zero, then the source, target and patch
+// CRC32s. XOR makes them work in both directions.
+// Ref: https://www.romhacking.net/documents/392/
+const MAGIC: &[u8] = b"UPS1";
+
+// Applies a UPS patch to a ROM. On error the ROM is left unchanged.
+pub fn apply(rom: &mut Rom, patch: &[u8]) -> Result<(), RomError> {
+    rom.replace_data(apply_to(rom.data(), patch)?)
+}
+
+// Produces the patched bytes. A patch given its own target as input undoes
+// itself. The CRC32s are checked either way.
+pub fn apply_to(input: &[u8], patch: &[u8]) -> Result<Vec<u8>, RomError> {
+    let (body, [source_crc, target_crc, patch_crc]) = split_footer(patch, MAGIC)?;
+    check_crc("patch", patch_crc, crc32(&patch[..patch.len() - 4]))?;
+    let mut reader = Reader::new(&body[MAGIC.len()..]);
+    let source_size = reader.size()?;
+    let target_size = reader.size()?;
+    let input_crc = crc32(input);
+    let (input_size, output_size, output_crc) =
+        if input_crc == target_crc && input_crc != source_crc {
+            (target_size, source_size, source_crc)
+        } else {
+            check_crc("source", source_crc, input_crc)?;
+            (source_size, target_size, target_crc)
+        };
+    if input.len() != input_size {
+        return Err(RomError::BadPatch("source size does not match"));
+    }
+
+    let mut output = input.to_vec();
+    output.resize(output_size, 0);
+    // Hunks cover the larger of the two files and nothing past it.
+    let end = source_size.max(target_size);
+    let past_end = || RomError::BadPatch("hunk past the end");
+    let mut pos = 0usize;
+    while !reader.is_empty() {
+        pos = usize::try_from(reader.number()?)
+            .ok()
+            .and_then(|skip| pos.checked_add(skip))
+            .filter(|&pos| pos <= end)
+            .ok_or_else(past_end)?;
+        loop {
+            let byte = reader.byte()?;
+            if byte == 0 {
+                pos = pos.checked_add(1).ok_or_else(past_end)?;
+                break;
+            }
+            // Bytes past the output's end only matter to the other direction.
+            if let Some(out) = output.get_mut(pos) {
+                *out ^= byte;
+            }
+            pos = pos.checked_add(1).ok_or_else(past_end)?;
+        }
+    }
+    check_crc("target", output_crc, crc32(&output))?;
+    Ok(output)
+}
+
+// Builds a UPS patch turning `source` into `target`.
+pub fn create(source: &Rom, target: &Rom) -> Vec<u8> {
+    create_from(source.data(), target.data())
+}
+
+pub fn create_from(source: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut patch = MAGIC.to_vec();
+    write_number(&mut patch, source.len() as u64);
+    write_number(&mut patch, target.len() as u64);
+    let len = source.len().max(target.len());
+    let xor = |i: usize| source.get(i).copied().unwrap_or(0) ^ target.get(i).copied().unwrap_or(0);
+    let mut pos = 0;
+    let mut i = 0;
+    while i < len {
+        if xor(i) == 0 {
+            i += 1;
+            continue;
+        }
+        write_number(&mut patch, (i - pos) as u64);
+        while i < len && xor(i) != 0 {
+            patch.push(xor(i));
+            i += 1;
+        }
+        patch.push(0);
+        // The terminator stands for the byte after the hunk.
+        i += 1;
+        pos = i;
+    }
+    patch.extend(crc32(source).to_le_bytes());
+    patch.extend(crc32(target).to_le_bytes());
+    patch.extend(crc32(&patch).to_le_bytes());
+    patch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source() -> Vec<u8> {
+        (0..0x2000).map(|i| (i * 31 + i / 97) as u8).collect()
+    }
+
+    #[test]
+    fn applies_in_both_directions() {
+        let source = source();
+        let mut target = source.clone();
+        target[0x100..0x180].fill(0x77);
+        target[0x400] ^= 1;
+        target.extend(b"appended");
+        for target in [&target[..], &target[..0x1000]] {
+            let patch = create_from(&source, target);
+            assert_eq!(apply_to(&source, &patch).unwrap(), target);
+            assert_eq!(apply_to(target, &patch).unwrap(), source);
+        }
+    }
+
+    #[test]
+    fn wrong_source_is_rejected() {
+        let source = source();
+        let patch = create_from(&source, &source[..0x100]);
+        assert!(matches!(
+            apply_to(&source[1..], &patch),
+            Err(RomError::CrcMismatch { what: "source", .. })
+        ));
+    }
+
+    #[test]
+    fn malformed_sizes() {
+        let source = source();
+        let mut patch = MAGIC.to_vec();
+        write_number(&mut patch, source.len() as u64);
+        write_number(&mut patch, 1 << 40);
+        patch.extend(crc32(&source).to_le_bytes());
+        patch.extend(0u32.to_le_bytes());
+        patch.extend(crc32(&patch).to_le_bytes());
+        assert!(matches!(
+            apply_to(&source, &patch),
+            Err(RomError::BadPatch("size too large"))
+        ));
+
+        // A hunk skip that would overflow the offset.
+        let mut patch = MAGIC.to_vec();
+        write_number(&mut patch, source.len() as u64);
+        write_number(&mut patch, source.len() as u64);
+        write_number(&mut patch, u64::MAX);
+        patch.extend([1, 0]);
+        patch.extend(crc32(&source).to_le_bytes());
+        patch.extend(crc32(&source).to_le_bytes());
+        patch.extend(crc32(&patch).to_le_bytes());
+        assert!(matches!(
+            apply_to(&source, &patch),
+            Err(RomError::BadPatch("hunk past the end"))
+        ));
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+// Ref: MC68000 family programmer's reference manual, table B-2, with the
+// interrupt levels the Mega Drive wires up.
+pub static VECTOR_NAMES: [&str; 64] = [
+    "Initial SSP",
+    "Reset PC",
+    "Bus error",
+    "Address error",
+    "Illegal instruction",
+    "Zero divide",
+    "CHK",
+    "TRAPV",
+    "Privilege violation",
+    "Trace",
+    "Line 1010 emulator",
+    "Line 1111 emulator",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Uninitialized interrupt",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Spurious interrupt",
+    "Level 1 autovector",
+    "Level 2 autovector (EXT)",
+    "Level 3 autovector",
+    "Level 4 autovector (HBlank)",
+    "Level 5 autovector",
+    "Level 6 autovector (VBlank)",
+    "Level 7 autovector",
+    "TRAP #0",
+    "TRAP #1",
+    "TRAP #2",
+    "TRAP #3",
+    "TRAP #4",
+    "TRAP #5",
+    "TRAP #6",
+    "TRAP #7",
+    "TRAP #8",
+    "TRAP #9",
+    "TRAP #10",
+    "TRAP #11",
+    "TRAP #12",
+    "TRAP #13",
+    "TRAP #14",
+    "TRAP #15",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+    "Reserved",
+];
+
+pub const VECTOR_COUNT: usize = 64;
+
+// The 64 longword exception vectors at 0x000-0x0FF.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VectorTable {
+    vectors: [u32; VECTOR_COUNT],
+    // Size of the ROM the table came from, for the out of ROM check.
+    rom_size: usize,
+}
+
+impl VectorTable {
+    // Reads the table from the start of a ROM image. Returns None if the
+    // image is too short to hold it.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let bytes = data.get(..VECTOR_COUNT * 4)?;
+        let mut vectors = [0; VECTOR_COUNT];
+        for (vector, chunk) in vectors.iter_mut().zip(bytes.chunks(4)) {
+            *vector = u32::from_be_bytes(chunk.try_into().unwrap());
+        }
+        Some(Self {
+            vectors,
+            rom_size: data.len(),
+        })
+    }
+    pub fn get(&self, index: usize) -> Option<u32> {
+        self.vectors.get(index).copied()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.vectors.iter().copied()
+    }
+    pub fn name(index: usize) -> &'static str {
+        VECTOR_NAMES.get(index).copied().unwrap_or("Invalid")
+    }
+
+    pub fn initial_ssp(&self) -> u32 {
+        self.vectors[0]
+    }
+    pub fn reset_pc(&self) -> u32 {
+        self.vectors[1]
+    }
+    pub fn bus_error(&self) -> u32 {
+        self.vectors[2]
+    }
+    pub fn address_error(&self) -> u32 {
+        self.vectors[3]
+    }
+    pub fn illegal_instruction(&self) -> u32 {
+        self.vectors[4]
+    }
+    pub fn zero_divide(&self) -> u32 {
+        self.vectors[5]
+    }
+    pub fn chk(&self) -> u32 {
+        self.vectors[6]
+    }
+    pub fn trapv(&self) -> u32 {
+        self.vectors[7]
+    }
+    pub fn privilege_violation(&self) -> u32 {
+        self.vectors[8]
+    }
+    pub fn trace(&self) -> u32 {
+        self.vectors[9]
+    }
+    pub fn line_a(&self) -> u32 {
+        self.vectors[10]
+    }
+    pub fn line_f(&self) -> u32 {
+        self.vectors[11]
+    }
+    pub fn spurious_interrupt(&self) -> u32 {
+        self.vectors[24]
+    }
+    // Interrupt levels 1 to 7.
+    pub fn autovector(&self, level: usize) -> Option<u32> {
+        (1..=7).contains(&level).then(|| self.vectors[24 + level])
+    }
+    // External interrupt from the controller ports (level 2).
+    pub fn external_interrupt(&self) -> u32 {
+        self.vectors[26]
+    }
+    // Horizontal blank (level 4).
+    pub fn hblank(&self) -> u32 {
+        self.vectors[28]
+    }
+    // Vertical blank (level 6).
+    pub fn vblank(&self) -> u32 {
+        self.vectors[30]
+    }
+    // TRAP #0 to #15.
+    pub fn trap(&self, n: usize) -> Option<u32> {
+        (n < 16).then(|| self.vectors[32 + n])
+    }
+
+    // Handlers used by more than one vector, with the vectors using them.
+    // The initial SSP is not a handler and is left out.
+    pub fn shared_handlers(&self) -> BTreeMap<u32, Vec<usize>> {
+        let mut handlers: BTreeMap<u32, Vec<usize>> = BTreeMap::new();
+        for (index, &address) in self.vectors.iter().enumerate().skip(1) {
+            handlers.entry(address).or_default().push(index);
+        }
+        handlers.retain(|_, vectors| vectors.len() > 1);
+        handlers
+    }
+    // Whether a handler address can be executed from ROM: inside the image
+    // and word aligned.
+    pub fn in_rom(&self, address: u32) -> bool {
+        (address as usize) < self.rom_size && address.is_multiple_of(2)
+    }
+    // Vectors, other than the initial SSP, whose handler is outside the ROM
+    // or misaligned.
+    pub fn out_of_rom(&self) -> Vec<usize> {
+        (1..VECTOR_COUNT)
+            .filter(|&index| !self.in_rom(self.vectors[index]))
+            .collect()
+    }
+}
+
+impl Display for VectorTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let shared = self.shared_handlers();
+        for (index, &address) in self.vectors.iter().enumerate() {
+            write!(f, "{:02X} {:<28} ${:08X}", index, Self::name(index), address)?;
+            if index > 0 {
+                if !self.in_rom(address) {
+                    write!(f, "  outside ROM")?;
+                }
+                if let Some(vectors) = shared.get(&address) {
+                    write!(f, "  shared by {} vectors", vectors.len())?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every handler at 0x200, except the ones given.
+    fn table(size: usize, handlers: &[(usize, u32)]) -> VectorTable {
+        let mut data = vec![0; size];
+        data[..4].copy_from_slice(&0x00FF_FE00u32.to_be_bytes());
+        for index in 1..VECTOR_COUNT {
+            let address = handlers
+                .iter()
+                .find(|&&(i, _)| i == index)
+                .map_or(0x200, |&(_, address)| address);
+            data[index * 4..index * 4 + 4].copy_from_slice(&address.to_be_bytes());
+        }
+        VectorTable::parse(&data).unwrap()
+    }
+
+    #[test]
+    fn parse_needs_the_whole_table() {
+        assert!(VectorTable::parse(&[0; 0xFF]).is_none());
+        let vectors = table(0x100, &[(1, 0x80), (30, 0x90)]);
+        assert_eq!(vectors.initial_ssp(), 0x00FF_FE00);
+        assert_eq!(vectors.reset_pc(), 0x80);
+        assert_eq!(vectors.vblank(), 0x90);
+        assert_eq!(vectors.autovector(6), Some(0x90));
+        assert_eq!(vectors.autovector(0), None);
+        assert_eq!(vectors.trap(16), None);
+    }
+
+    #[test]
+    fn shared_handlers() {
+        let vectors = table(0x400, &[(1, 0x300), (28, 0x310), (30, 0x310)]);
+        let shared = vectors.shared_handlers();
+        assert_eq!(shared.len(), 2);
+        assert_eq!(shared[&0x310], [28, 30]);
+        assert_eq!(shared[&0x200].len(), VECTOR_COUNT - 4);
+        assert!(!shared.contains_key(&0x300));
+        // The initial SSP never counts, even when it equals a handler.
+        let vectors = table(0x400, &[(1, 0x00FF_FE00)]);
+        assert!(!vectors.shared_handlers().contains_key(&0x00FF_FE00));
+    }
+
+    #[test]
+    fn handlers_outside_rom() {
+        let vectors = table(
+            0x400,
+            &[(2, 0x201), (3, 0x400), (4, 0x3FE), (5, 0x00FF_0000)],
+        );
+        assert!(vectors.in_rom(0x3FE));
+        assert!(!vectors.in_rom(0x201));
+        assert!(!vectors.in_rom(0x400));
+        assert_eq!(vectors.out_of_rom(), [2, 3, 5]);
+        // The stack pointer points to RAM, which is fine.
+        assert!(!vectors.out_of_rom().contains(&0));
+        let text = vectors.to_string();
+        assert_eq!(text.lines().count(), VECTOR_COUNT);
+        assert!(text.lines().nth(3).unwrap().ends_with("outside ROM"));
+    }
+}